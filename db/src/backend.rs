@@ -0,0 +1,86 @@
+//! Where albums and items are read from: a beets database, or memory.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "sqlite")]
+use rusqlite::Connection;
+
+use super::filter::Filter;
+use super::{Album, Error, Item};
+
+/// A source of albums and items.
+pub trait Backend {
+    /// The albums that pass `filter`, in its order.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the albums cannot be read.
+    fn albums(&self, filter: &Filter) -> Result<Vec<Album>, Error>;
+
+    /// The items that pass `filter`, in its order.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the items cannot be read.
+    fn items(&self, filter: &Filter) -> Result<Vec<Item>, Error>;
+}
+
+#[cfg(feature = "sqlite")]
+impl Backend for Connection {
+    fn albums(&self, filter: &Filter) -> Result<Vec<Album>, Error> {
+        Album::read_filtered(self, filter)
+    }
+
+    fn items(&self, filter: &Filter) -> Result<Vec<Item>, Error> {
+        Item::read_filtered(self, filter)
+    }
+}
+
+/// Albums and items kept in memory, which needs no database, e.g. for tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Memory {
+    albums: BTreeMap<u32, Album>,
+    items: BTreeMap<u32, Item>,
+}
+
+impl Memory {
+    /// Keep the given albums and items. Later entries replace earlier ones
+    /// with the same id.
+    #[must_use]
+    pub fn new(albums: Vec<Album>, items: Vec<Item>) -> Self {
+        Self {
+            albums: albums.into_iter().map(|album| (album.id, album)).collect(),
+            items: items.into_iter().map(|item| (item.id, item)).collect(),
+        }
+    }
+
+    /// Add an album, returning the one it replaces, if any.
+    pub fn insert_album(&mut self, album: Album) -> Option<Album> {
+        self.albums.insert(album.id, album)
+    }
+
+    /// Add an item, returning the one it replaces, if any.
+    pub fn insert_item(&mut self, item: Item) -> Option<Item> {
+        self.items.insert(item.id, item)
+    }
+
+    /// Remove the album with the given id, but not its items.
+    pub fn remove_album(&mut self, id: u32) -> Option<Album> {
+        self.albums.remove(&id)
+    }
+
+    /// Remove the item with the given id.
+    pub fn remove_item(&mut self, id: u32) -> Option<Item> {
+        self.items.remove(&id)
+    }
+}
+
+impl Backend for Memory {
+    fn albums(&self, filter: &Filter) -> Result<Vec<Album>, Error> {
+        Ok(filter.apply(self.albums.values().cloned().collect()))
+    }
+
+    fn items(&self, filter: &Filter) -> Result<Vec<Item>, Error> {
+        Ok(filter.apply(self.items.values().cloned().collect()))
+    }
+}
@@ -0,0 +1,83 @@
+//! Export the albums or items of a beets library, e.g. for a notebook.
+
+#![deny(clippy::pedantic)]
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+
+use beet_db::config::Config;
+use beet_db::export::{Export, Format};
+use beet_db::{Album, Error, Item};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "beet-export")]
+#[structopt(about = "export a beets library for analysis")]
+#[structopt(raw(setting = "structopt::clap::AppSettings::ColoredHelp"))]
+#[structopt(rename_all = "kebab-case")]
+struct Cli {
+    /// Export albums instead of items.
+    #[structopt(long)]
+    albums: bool,
+    /// The format to write: `jsonl`, `csv` or `columnar`.
+    #[structopt(short, long, default_value = "jsonl")]
+    format: Format,
+    /// The fields and flexible attributes to write, separated by commas.
+    /// Defaults to every field. Ignored for JSON Lines.
+    #[structopt(short, long, raw(use_delimiter = "true"))]
+    columns: Vec<String>,
+    /// The file to write to. Defaults to standard output.
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// Path to your beet database. Defaults to the `library` in your beets
+    /// configuration.
+    #[structopt(parse(from_os_str))]
+    db_path: Option<PathBuf>,
+}
+
+fn main() {
+    let cli = Cli::from_args();
+
+    let out: Box<dyn Write> = match &cli.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(err) => {
+                eprintln!("Could not create {}: {err}", path.display());
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+
+    match export(&cli, BufWriter::new(out)) {
+        Ok(count) => {
+            let table = if cli.albums { "albums" } else { "items" };
+            eprintln!("Exported {count} {table}.");
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn export<W: Write>(cli: &Cli, out: W) -> Result<u64, Error> {
+    let db_path = match &cli.db_path {
+        Some(db_path) => db_path.clone(),
+        None => Config::load()?.library,
+    };
+    let conn = beet_db::open(db_path)?;
+
+    let mut export = Export::new(cli.format);
+    if !cli.columns.is_empty() {
+        export = export.columns(cli.columns.clone());
+    }
+
+    if cli.albums {
+        export.write(Album::iter(&conn), out)
+    } else {
+        export.write(Item::iter(&conn), out)
+    }
+}
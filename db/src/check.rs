@@ -0,0 +1,167 @@
+//! Check a library against the files it refers to, e.g. to find tracks that
+//! were moved or edited outside of beets.
+//!
+//! beets does not record the size of files, so a file is only considered
+//! modified if its modification time differs from the item's `mtime`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "serde")]
+use super::path;
+use super::Library;
+
+/// How far, in seconds, a file's modification time may be from the `mtime`
+/// that beets recorded for it.
+const MTIME_TOLERANCE: f64 = 1.0;
+
+/// Something wrong with a library.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Problem {
+    /// There is no file at the item's path.
+    MissingFile {
+        item: u32,
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
+        path: PathBuf,
+    },
+    /// There is no file at the album's art path.
+    MissingArt {
+        album: u32,
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
+        path: PathBuf,
+    },
+    /// The file could not be checked, e.g. for lack of permission.
+    Unreadable {
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
+        path: PathBuf,
+        error: String,
+    },
+    /// The item's file was modified since beets last read or wrote it.
+    Modified {
+        item: u32,
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
+        path: PathBuf,
+        /// When beets recorded the file as last modified, in seconds since
+        /// the Unix epoch.
+        mtime: f64,
+        /// When the file was actually last modified.
+        modified: f64,
+    },
+    /// The item belongs to an album that does not exist.
+    MissingAlbum { item: u32, album_id: u32 },
+    /// The album has no items.
+    EmptyAlbum { album: u32 },
+    /// More than one item has the same path.
+    DuplicatePath {
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
+        path: PathBuf,
+        items: Vec<u32>,
+    },
+}
+
+/// Everything wrong with a library, in the order it was found: items by id,
+/// then albums by id, then duplicate paths.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Report {
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Whether nothing is wrong.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Check every item and album in `library`, including their files.
+#[must_use]
+pub fn check(library: &Library) -> Report {
+    let mut problems = Vec::new();
+    let mut paths = BTreeMap::<_, Vec<_>>::new();
+
+    for item in library.items() {
+        match modified(&item.path) {
+            Ok(modified) if item.mtime > 0.0 && (modified - item.mtime).abs() > MTIME_TOLERANCE => {
+                problems.push(Problem::Modified {
+                    item: item.id,
+                    path: item.path.clone(),
+                    mtime: item.mtime,
+                    modified,
+                });
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                problems.push(Problem::MissingFile {
+                    item: item.id,
+                    path: item.path.clone(),
+                });
+            }
+            Err(err) => problems.push(unreadable(&item.path, &err)),
+        }
+
+        if let Some(album_id) = item.album_id {
+            if item.album().is_none() {
+                problems.push(Problem::MissingAlbum {
+                    item: item.id,
+                    album_id,
+                });
+            }
+        }
+
+        paths.entry(&item.as_item().path).or_default().push(item.id);
+    }
+
+    for album in library.albums() {
+        if let Some(artpath) = &album.artpath {
+            match fs::metadata(artpath) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    problems.push(Problem::MissingArt {
+                        album: album.id,
+                        path: artpath.clone(),
+                    });
+                }
+                Err(err) => problems.push(unreadable(artpath, &err)),
+            }
+        }
+
+        if album.items().next().is_none() {
+            problems.push(Problem::EmptyAlbum { album: album.id });
+        }
+    }
+
+    for (path, items) in paths {
+        if items.len() > 1 {
+            problems.push(Problem::DuplicatePath {
+                path: path.clone(),
+                items,
+            });
+        }
+    }
+
+    Report { problems }
+}
+
+/// When the file at `path` was last modified, in seconds since the Unix
+/// epoch.
+fn modified(path: &Path) -> io::Result<f64> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(match modified.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs_f64(),
+        Err(err) => -err.duration().as_secs_f64(),
+    })
+}
+
+fn unreadable(path: &Path, err: &io::Error) -> Problem {
+    Problem::Unreadable {
+        path: path.to_path_buf(),
+        error: err.to_string(),
+    }
+}
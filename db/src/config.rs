@@ -0,0 +1,226 @@
+//! Find and read the beets configuration, to locate the user's library.
+//!
+//! This follows beets: `config.yaml` is read from `$BEETSDIR` if it is set,
+//! and otherwise from `~/.config/beets` (or `%APPDATA%\beets` on Windows).
+//! Each file named by its `include:` option is read too, and overrides it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+use super::Error;
+
+/// The name of the main configuration file in the configuration directory.
+const CONFIG_FILE: &str = "config.yaml";
+
+/// The path formats that beets uses unless they are configured.
+const DEFAULT_PATHS: &[(&str, &str)] = &[
+    ("default", "$albumartist/$album%aunique{}/$track $title"),
+    ("singleton", "Non-Album/$artist/$title"),
+    ("comp", "Compilations/$album%aunique{}/$track $title"),
+];
+
+/// The parts of the beets configuration that describe the library.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// The directory holding `config.yaml`, which relative paths are
+    /// resolved against.
+    pub dir: PathBuf,
+    /// The path to the library database.
+    pub library: PathBuf,
+    /// The directory that music is kept in.
+    pub directory: PathBuf,
+    /// The path formats, as pairs of a query (or `default`, `singleton` or
+    /// `comp`) and a template, in order of priority.
+    pub paths: Vec<(String, String)>,
+    /// The names of the plugins that are enabled.
+    pub plugins: Vec<String>,
+}
+
+/// A parsed configuration file.
+struct Source {
+    path: PathBuf,
+    values: Mapping,
+}
+
+impl Config {
+    /// The configuration directory, or `None` if `$BEETSDIR` is not set
+    /// and there is no home directory.
+    #[must_use]
+    pub fn dir() -> Option<PathBuf> {
+        if let Some(dir) = env::var_os("BEETSDIR") {
+            return Some(expand_home(Path::new(&dir)));
+        }
+
+        if cfg!(windows) {
+            return env::var_os("APPDATA").map(|dir| Path::new(&dir).join("beets"));
+        }
+
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join("beets"))
+    }
+
+    /// Read the configuration from its usual directory (see
+    /// [`dir`](#method.dir)).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration directory cannot be found, or see
+    /// [`from_dir`](#method.from_dir).
+    pub fn load() -> Result<Self, Error> {
+        Self::from_dir(Self::dir().ok_or(Error::NoConfig)?)
+    }
+
+    /// Read the configuration in `dir`. If there is no `config.yaml` there,
+    /// this is beets' default configuration.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a configuration file (or a file it includes) cannot be read
+    /// or parsed, or has a value of the wrong type.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let main = dir.join(CONFIG_FILE);
+
+        // In order of priority, so each include overrides the ones before it
+        // and the main file.
+        let mut sources = Vec::new();
+        if main.is_file() {
+            let main = Source::read(main)?;
+            for include in main.strings("include")?.unwrap_or_default() {
+                sources.insert(0, Source::read(resolve(dir, &include))?);
+            }
+            sources.push(main);
+        }
+
+        Self::from_sources(dir, &sources)
+    }
+
+    fn from_sources(dir: &Path, sources: &[Source]) -> Result<Self, Error> {
+        let mut library = dir.join("library.db");
+        let mut directory = expand_home(Path::new("~/Music"));
+        let mut plugins = None;
+        let mut paths = Vec::<(String, String)>::new();
+
+        for source in sources.iter().rev() {
+            if let Some(path) = source.string("library")? {
+                library = resolve(dir, &path);
+            }
+            if let Some(path) = source.string("directory")? {
+                directory = resolve(dir, &path);
+            }
+            if let Some(names) = source.strings("plugins")? {
+                plugins = Some(names);
+            }
+        }
+
+        for source in sources {
+            for (query, template) in source.pairs("paths")? {
+                if !paths.iter().any(|(q, _)| *q == query) {
+                    paths.push((query, template));
+                }
+            }
+        }
+        for (query, template) in DEFAULT_PATHS {
+            if !paths.iter().any(|(q, _)| q == query) {
+                paths.push(((*query).to_string(), (*template).to_string()));
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            library,
+            directory,
+            paths,
+            plugins: plugins.unwrap_or_default(),
+        })
+    }
+}
+
+impl Source {
+    fn read(path: PathBuf) -> Result<Self, Error> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+        let values = match serde_yaml::from_str::<Option<Mapping>>(&text) {
+            Ok(values) => values.unwrap_or_default(),
+            Err(source) => return Err(Error::ConfigSyntax { path, source }),
+        };
+        Ok(Self { path, values })
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self.values.get(&Value::from(key)) {
+            None | Some(Value::Null) => None,
+            value => value,
+        }
+    }
+
+    fn invalid(&self, key: &'static str) -> Error {
+        Error::InvalidConfig {
+            path: self.path.clone(),
+            key,
+        }
+    }
+
+    /// A string option.
+    fn string(&self, key: &'static str) -> Result<Option<String>, Error> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    /// An option that is either a list of strings, or a single string of
+    /// whitespace-separated words.
+    fn strings(&self, key: &'static str) -> Result<Option<Vec<String>>, Error> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.split_whitespace().map(String::from).collect())),
+            Some(Value::Sequence(values)) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => Ok(s.clone()),
+                    _ => Err(self.invalid(key)),
+                })
+                .collect::<Result<_, _>>()
+                .map(Some),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    /// An option that maps strings to strings, in order.
+    fn pairs(&self, key: &'static str) -> Result<Vec<(String, String)>, Error> {
+        match self.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::Mapping(values)) => values
+                .iter()
+                .map(|pair| match pair {
+                    (Value::String(k), Value::String(v)) => Ok((k.clone(), v.clone())),
+                    _ => Err(self.invalid(key)),
+                })
+                .collect(),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+}
+
+/// Replace a leading `~` with the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Resolve a path from the configuration, relative to its directory.
+fn resolve(dir: &Path, path: &str) -> PathBuf {
+    dir.join(expand_home(Path::new(path)))
+}
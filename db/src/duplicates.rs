@@ -0,0 +1,190 @@
+//! Find items and albums that are probably the same recording or release,
+//! e.g. because it was imported again in a different format.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{Item, Library};
+
+/// How far apart, in seconds, the lengths of items with the same artist and
+/// title may be for them to be considered duplicates.
+pub const DEFAULT_LENGTH_TOLERANCE: f64 = 2.0;
+
+/// Formats that do not lose information, as beets names them.
+const LOSSLESS: &[&str] = &[
+    "AIFF", "ALAC", "APE", "DSF", "FLAC", "TTA", "WAV", "WavPack",
+];
+
+/// Why the members of a cluster are considered duplicates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Match {
+    /// The same `mb_trackid`.
+    MbTrackId,
+    /// The same `acoustid_id`.
+    AcoustId,
+    /// The same `acoustid_fingerprint`.
+    Fingerprint,
+    /// The same artist and title, ignoring case and punctuation, and about
+    /// the same length.
+    Metadata,
+    /// The same `mb_albumid`.
+    MbAlbumId,
+}
+
+/// Items or albums that are probably duplicates of each other.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Cluster {
+    pub by: Match,
+    /// The ids of the members, in order.
+    pub ids: Vec<u32>,
+    /// The id of the member of the best quality, i.e. the one to keep.
+    pub best: u32,
+}
+
+/// Every cluster of duplicates in a library.
+///
+/// A group of items that matches in more than one way is only reported for
+/// the first of them, in the order of [`Match`](enum.Match.html).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Duplicates {
+    pub items: Vec<Cluster>,
+    pub albums: Vec<Cluster>,
+}
+
+impl Duplicates {
+    /// Whether there are no duplicates.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.albums.is_empty()
+    }
+}
+
+/// Find the duplicate items and albums in `library`.
+///
+/// Items only match by metadata if their lengths are within
+/// `length_tolerance` seconds of each other, or of another item that does.
+#[must_use]
+pub fn find(library: &Library, length_tolerance: f64) -> Duplicates {
+    let by_field = |field: fn(&Item) -> &str| {
+        group(library.items().map(|item| (field(item.as_item()), item.id)))
+    };
+    let candidates = vec![
+        (Match::MbTrackId, by_field(|item| &item.mb_trackid)),
+        (Match::AcoustId, by_field(|item| &item.acoustid_id)),
+        (
+            Match::Fingerprint,
+            by_field(|item| &item.acoustid_fingerprint),
+        ),
+        (Match::Metadata, by_metadata(library, length_tolerance)),
+    ];
+
+    let mut seen = BTreeSet::new();
+    let mut items = Vec::new();
+    for (by, groups) in candidates {
+        for ids in groups {
+            if seen.insert(ids.clone()) {
+                let best = best(&ids, |id| library.item(id).map(|item| quality(&item)));
+                items.push(Cluster { by, ids, best });
+            }
+        }
+    }
+
+    let albums = group(
+        library
+            .albums()
+            .map(|album| (album.as_album().mb_albumid.as_str(), album.id)),
+    )
+    .into_iter()
+    .map(|ids| {
+        // An album is only as good as its worst track.
+        let best = best(&ids, |id| {
+            library
+                .album(id)
+                .and_then(|album| album.items().map(|item| quality(&item)).min())
+        });
+        Cluster {
+            by: Match::MbAlbumId,
+            ids,
+            best,
+        }
+    })
+    .collect();
+
+    Duplicates { items, albums }
+}
+
+/// The ids that share a key with another, ignoring empty keys.
+fn group<'a>(keys: impl Iterator<Item = (&'a str, u32)>) -> Vec<Vec<u32>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for (key, id) in keys {
+        if !key.is_empty() {
+            groups.entry(key).or_default().push(id);
+        }
+    }
+    groups.into_values().filter(|ids| ids.len() > 1).collect()
+}
+
+fn by_metadata(library: &Library, length_tolerance: f64) -> Vec<Vec<u32>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for item in library.items() {
+        let title = normalize(&item.title);
+        if !title.is_empty() {
+            groups
+                .entry((normalize(&item.artist), title))
+                .or_default()
+                .push((item.length, item.id));
+        }
+    }
+
+    let mut clusters = Vec::new();
+    for mut lengths in groups.into_values() {
+        lengths.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut cluster = Vec::<u32>::new();
+        let mut previous = None;
+        for (length, id) in lengths {
+            if previous.is_some_and(|previous| length - previous > length_tolerance) {
+                clusters.push(cluster.split_off(0));
+            }
+            cluster.push(id);
+            previous = Some(length);
+        }
+        clusters.push(cluster);
+    }
+
+    for cluster in &mut clusters {
+        cluster.sort_unstable();
+    }
+    clusters.retain(|ids| ids.len() > 1);
+    clusters.sort();
+    clusters
+}
+
+/// Lowercase words, without punctuation.
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lossless formats first, then by bit depth, sample rate and bitrate.
+fn quality(item: &Item) -> (bool, u16, u32, u32) {
+    let lossless = LOSSLESS
+        .iter()
+        .any(|format| format.eq_ignore_ascii_case(&item.format));
+    (lossless, item.bitdepth, item.samplerate, item.bitrate)
+}
+
+/// The id with the best quality, or the lowest of those tied for it.
+fn best<Q: Ord>(ids: &[u32], quality: impl Fn(u32) -> Q) -> u32 {
+    ids.iter()
+        .copied()
+        .max_by_key(|&id| (quality(id), Reverse(id)))
+        .unwrap_or_default()
+}
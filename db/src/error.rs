@@ -0,0 +1,143 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[cfg(feature = "sqlite")]
+use rusqlite::ErrorCode;
+
+/// Everything that can go wrong while reading or writing a beets database.
+///
+/// Some variants only exist with the features that can cause them, such as
+/// `sqlite`, so matches on it need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// There is no file at the given path.
+    NotFound(PathBuf),
+    /// The file exists, but is not a `SQLite` database with the beets tables.
+    NotBeets(PathBuf),
+    /// A table is missing a column that this crate cannot do without.
+    UnsupportedSchema {
+        table: &'static str,
+        column: &'static str,
+    },
+    /// A value could not be converted to the type of its field.
+    #[cfg(feature = "sqlite")]
+    BadValue {
+        table: &'static str,
+        /// The `id` (rowid) of the offending row.
+        row: i64,
+        column: &'static str,
+        source: rusqlite::Error,
+    },
+    /// There is no entry with the given id.
+    NoSuchEntry { table: &'static str, id: u32 },
+    /// A change names a field that the table does not have.
+    UnknownField { table: &'static str, field: String },
+    /// A change names a field that cannot be written, such as `id`.
+    ReadOnlyField {
+        table: &'static str,
+        field: &'static str,
+    },
+    /// A flexible attribute has the same name as one of the table's fields.
+    NotFlexible { table: &'static str, field: String },
+    /// Neither `$BEETSDIR` nor the home directory is set, so there is no
+    /// configuration to read.
+    NoConfig,
+    /// A file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A configuration file is not valid YAML.
+    #[cfg(feature = "sqlite")]
+    ConfigSyntax {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    /// A configuration file has a value of the wrong type.
+    InvalidConfig { path: PathBuf, key: &'static str },
+    /// An export could not be written, or read back.
+    Export(std::io::Error),
+    /// Any other error reported by `SQLite`.
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+}
+
+#[cfg(feature = "sqlite")]
+impl Error {
+    /// Whether `SQLite` refused to read the file because it is not a database.
+    pub(crate) fn is_not_a_database(err: &rusqlite::Error) -> bool {
+        match err {
+            rusqlite::Error::SqliteFailure(e, _) => e.code == ErrorCode::NotADatabase,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound(path) => write!(f, "No database found at {}", path.display()),
+            Error::NotBeets(path) => write!(f, "{} is not a beets database", path.display()),
+            Error::UnsupportedSchema { table, column } => write!(
+                f,
+                "Unsupported schema version: table `{table}` has no `{column}` column"
+            ),
+            #[cfg(feature = "sqlite")]
+            Error::BadValue {
+                table,
+                row,
+                column,
+                source,
+            } => write!(
+                f,
+                "Row {row} of table `{table}` has a bad value in column `{column}`: {source}"
+            ),
+            Error::NoSuchEntry { table, id } => {
+                write!(f, "There is no entry with id {id} in table `{table}`")
+            }
+            Error::UnknownField { table, field } => {
+                write!(f, "Table `{table}` has no field named `{field}`")
+            }
+            Error::ReadOnlyField { table, field } => {
+                write!(f, "Field `{field}` of table `{table}` cannot be changed")
+            }
+            Error::NotFlexible { table, field } => write!(
+                f,
+                "`{field}` is a field of table `{table}`, not a flexible attribute"
+            ),
+            Error::NoConfig => write!(f, "Could not find the beets configuration directory"),
+            Error::Io { path, source } => write!(f, "Could not read {}: {source}", path.display()),
+            #[cfg(feature = "sqlite")]
+            Error::ConfigSyntax { path, source } => {
+                write!(f, "Could not parse {}: {source}", path.display())
+            }
+            Error::InvalidConfig { path, key } => {
+                write!(f, "Option `{key}` in {} has the wrong type", path.display())
+            }
+            #[cfg(feature = "sqlite")]
+            Error::Sqlite(err) => write!(f, "SQLite error: {err}"),
+            Error::Export(err) => write!(f, "Could not export: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Error::BadValue { source, .. } | Error::Sqlite(source) => Some(source),
+            Error::Io { source, .. } | Error::Export(source) => Some(source),
+            #[cfg(feature = "sqlite")]
+            Error::ConfigSyntax { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
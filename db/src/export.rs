@@ -0,0 +1,425 @@
+//! Export snapshots of albums or items for analysis elsewhere, e.g. in a
+//! notebook.
+//!
+//! Entries are written as they are read, so a whole library can be exported
+//! without holding it in memory. There are three formats:
+//!
+//! - JSON Lines: each entry on its own line, serialized as a JSON object
+//!   with its flexible attributes, as `beet-up` serves it, plus the `mtime`
+//!   and `added` timestamps that `beet-up` leaves out.
+//! - CSV: a header row, then a row of the chosen columns for each entry.
+//!   Paths are written as their exact bytes, so a path that is not valid
+//!   UTF-8 makes the file invalid UTF-8 too.
+//! - Columnar: a compact binary format which stores each column of a group
+//!   of rows together, so that a column can be read without the others.
+//!
+//! # The columnar format
+//!
+//! All numbers are little-endian.
+//!
+//! ```text
+//! file   = "BEETCOL1" group* end
+//! group  = rows:u32 columns:u32 column*
+//! end    = 0:u32
+//! column = name:str type:u8 present:bitmap value*
+//! str    = length:u32 byte*
+//! ```
+//!
+//! The bitmap has a bit for each row, least significant first, which is set
+//! if the value is not `NULL`. Only those values follow it, as an `i64`
+//! (type 1), an `f64` (type 2), a UTF-8 `str` (type 3) or a `str` of raw
+//! bytes (type 4), such as a path. Each group has its own columns: by
+//! default every field, then every flexible attribute that its rows have.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use super::filter::Entity;
+use super::{Error, Value};
+
+/// The bytes that a file in the columnar format starts with.
+const MAGIC: &[u8; 8] = b"BEETCOL1";
+
+/// The fields that JSON Lines adds to what `beet-up` serializes.
+const TIMESTAMPS: &[&str] = &["mtime", "added"];
+
+/// How many rows the columnar format groups together by default.
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 1000;
+
+/// A format to export to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    JsonLines,
+    Csv,
+    Columnar,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "jsonl" | "json-lines" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            "columnar" => Ok(Format::Columnar),
+            _ => Err(format!(
+                "Unknown format `{s}`, expected `jsonl`, `csv` or `columnar`"
+            )),
+        }
+    }
+}
+
+/// How to export a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Export {
+    pub format: Format,
+    /// The fields and flexible attributes to write as columns, in order, for
+    /// CSV and the columnar format. JSON Lines always has every field.
+    pub columns: Option<Vec<String>>,
+    /// How many rows the columnar format groups together.
+    pub row_group_size: usize,
+}
+
+/// The type of a column in the columnar format, in order of precedence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Integer = 1,
+    Real = 2,
+    Text = 3,
+    Bytes = 4,
+}
+
+impl Export {
+    /// Export to `format`, with the default columns.
+    #[must_use]
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            columns: None,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+        }
+    }
+
+    /// Write only the given columns.
+    #[must_use]
+    pub fn columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    /// Group up to `size` rows together in the columnar format.
+    #[must_use]
+    pub fn row_group_size(mut self, size: usize) -> Self {
+        self.row_group_size = size.max(1);
+        self
+    }
+
+    /// Write each of `entries` to `out` as it is read, returning how many
+    /// were written.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first error in `entries`, or if `out` cannot be
+    /// written to.
+    pub fn write<T, I, W>(&self, entries: I, out: W) -> Result<u64, Error>
+    where
+        T: Entity + Serialize,
+        I: IntoIterator<Item = Result<T, Error>>,
+        W: Write,
+    {
+        match self.format {
+            Format::JsonLines => json_lines(entries, out),
+            Format::Csv => self.csv(entries, out),
+            Format::Columnar => self.columnar(entries, out),
+        }
+    }
+
+    fn csv<T, I, W>(&self, entries: I, out: W) -> Result<u64, Error>
+    where
+        T: Entity,
+        I: IntoIterator<Item = Result<T, Error>>,
+        W: Write,
+    {
+        let columns = match &self.columns {
+            Some(columns) => columns.clone(),
+            None => T::FIELDS.iter().map(|&f| f.to_string()).collect(),
+        };
+
+        let mut writer = csv::Writer::from_writer(out);
+        writer.write_record(&columns).map_err(export_error)?;
+        let mut count = 0;
+        for entry in entries {
+            let entry = entry?;
+            let row = columns.iter().map(|column| match entry.get(column) {
+                Some(Value::Bytes(bytes)) => bytes,
+                Some(value) => value
+                    .as_text()
+                    .map(|text| text.into_owned().into_bytes())
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            });
+            writer.write_record(row).map_err(export_error)?;
+            count += 1;
+        }
+        writer.flush().map_err(export_error)?;
+        Ok(count)
+    }
+
+    fn columnar<T, I, W>(&self, entries: I, mut out: W) -> Result<u64, Error>
+    where
+        T: Entity,
+        I: IntoIterator<Item = Result<T, Error>>,
+        W: Write,
+    {
+        out.write_all(MAGIC).map_err(export_error)?;
+        let mut count = 0;
+        let mut group = Vec::with_capacity(self.row_group_size);
+        for entry in entries {
+            group.push(entry?);
+            if group.len() == self.row_group_size {
+                self.write_group(&mut out, &group).map_err(export_error)?;
+                count += group.len() as u64;
+                group.clear();
+            }
+        }
+        if !group.is_empty() {
+            self.write_group(&mut out, &group).map_err(export_error)?;
+            count += group.len() as u64;
+        }
+        write_len(&mut out, 0).map_err(export_error)?;
+        out.flush().map_err(export_error)?;
+        Ok(count)
+    }
+
+    fn write_group<T: Entity, W: Write>(&self, out: &mut W, group: &[T]) -> io::Result<()> {
+        let columns = self.columns.clone().unwrap_or_else(|| {
+            let flex = group
+                .iter()
+                .flat_map(|entry| entry.flex().keys())
+                .collect::<BTreeSet<_>>();
+            T::FIELDS
+                .iter()
+                .map(|&f| f.to_string())
+                .chain(flex.into_iter().cloned())
+                .collect()
+        });
+
+        write_len(out, group.len())?;
+        write_len(out, columns.len())?;
+        for column in &columns {
+            let values = group
+                .iter()
+                .map(|entry| entry.get(column).unwrap_or(Value::Null))
+                .collect::<Vec<_>>();
+            write_column(out, column, &values)?;
+        }
+        Ok(())
+    }
+}
+
+fn json_lines<T, I, W>(entries: I, mut out: W) -> Result<u64, Error>
+where
+    T: Entity + Serialize,
+    I: IntoIterator<Item = Result<T, Error>>,
+    W: Write,
+{
+    let mut count = 0;
+    for entry in entries {
+        let entry = entry?;
+        let mut json = serde_json::to_value(&entry).map_err(export_error)?;
+        if let serde_json::Value::Object(object) = &mut json {
+            for &field in TIMESTAMPS {
+                if let Some(time) = entry.get(field).and_then(|value| value.as_real()) {
+                    object.insert(field.to_string(), time.into());
+                }
+            }
+        }
+        serde_json::to_writer(&mut out, &json).map_err(export_error)?;
+        out.write_all(b"\n").map_err(export_error)?;
+        count += 1;
+    }
+    out.flush().map_err(export_error)?;
+    Ok(count)
+}
+
+fn export_error<E: Into<io::Error>>(err: E) -> Error {
+    Error::Export(err.into())
+}
+
+fn write_len<W: Write>(out: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long to export"))?;
+    out.write_all(&len.to_le_bytes())
+}
+
+fn write_str<W: Write>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write_len(out, bytes.len())?;
+    out.write_all(bytes)
+}
+
+fn write_column<W: Write>(out: &mut W, name: &str, values: &[Value]) -> io::Result<()> {
+    let kind = values
+        .iter()
+        .map(|value| match value {
+            Value::Null | Value::Integer(_) => Kind::Integer,
+            Value::Real(_) => Kind::Real,
+            Value::Text(_) => Kind::Text,
+            Value::Bytes(_) => Kind::Bytes,
+        })
+        .max()
+        .unwrap_or(Kind::Integer);
+
+    write_str(out, name.as_bytes())?;
+    out.write_all(&[kind as u8])?;
+
+    let mut present = vec![0_u8; values.len().div_ceil(8)];
+    for (i, value) in values.iter().enumerate() {
+        if *value != Value::Null {
+            present[i / 8] |= 1 << (i % 8);
+        }
+    }
+    out.write_all(&present)?;
+
+    for value in values.iter().filter(|value| **value != Value::Null) {
+        match (kind, value) {
+            (Kind::Integer, Value::Integer(i)) => out.write_all(&i.to_le_bytes())?,
+            (Kind::Real, _) => out.write_all(&value.as_real().unwrap_or_default().to_le_bytes())?,
+            (Kind::Bytes, Value::Bytes(bytes)) => write_str(out, bytes)?,
+            _ => write_str(out, value.as_text().unwrap_or_default().as_bytes())?,
+        }
+    }
+    Ok(())
+}
+
+/// A group of rows read back from the columnar format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowGroup {
+    pub rows: usize,
+    /// The name and values of each column, in order.
+    pub columns: Vec<(String, Vec<Value>)>,
+}
+
+impl RowGroup {
+    /// The values of the column `name`, if the group has it.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<&[Value]> {
+        self.columns
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, values)| values.as_slice())
+    }
+}
+
+/// Reads the columnar format a row group at a time.
+pub struct ColumnarReader<R> {
+    input: R,
+    done: bool,
+}
+
+impl<R: Read> ColumnarReader<R> {
+    /// Start reading from `input`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `input` cannot be read, or is not in the columnar format.
+    pub fn new(mut input: R) -> Result<Self, Error> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic).map_err(export_error)?;
+        if &magic != MAGIC {
+            return Err(export_error(invalid("not in the columnar format")));
+        }
+        Ok(Self { input, done: false })
+    }
+
+    fn read_group(&mut self) -> io::Result<Option<RowGroup>> {
+        let rows = self.read_len()?;
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let mut columns = Vec::new();
+        for _ in 0..self.read_len()? {
+            let name = String::from_utf8(self.read_str()?)
+                .map_err(|_| invalid("column name is not UTF-8"))?;
+            let mut kind = [0];
+            self.input.read_exact(&mut kind)?;
+            let mut present = vec![0; rows.div_ceil(8)];
+            self.input.read_exact(&mut present)?;
+
+            let mut values = Vec::with_capacity(rows);
+            for i in 0..rows {
+                if present[i / 8] & (1 << (i % 8)) == 0 {
+                    values.push(Value::Null);
+                    continue;
+                }
+                values.push(match kind[0] {
+                    1 => Value::Integer(i64::from_le_bytes(self.read_array()?)),
+                    2 => Value::Real(f64::from_le_bytes(self.read_array()?)),
+                    3 => Value::Text(
+                        String::from_utf8(self.read_str()?)
+                            .map_err(|_| invalid("text is not UTF-8"))?,
+                    ),
+                    4 => Value::Bytes(self.read_str()?),
+                    _ => return Err(invalid("unknown column type")),
+                });
+            }
+            columns.push((name, values));
+        }
+
+        Ok(Some(RowGroup { rows, columns }))
+    }
+
+    fn read_array(&mut self) -> io::Result<[u8; 8]> {
+        let mut bytes = [0; 8];
+        self.input.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_len(&mut self) -> io::Result<usize> {
+        let mut bytes = [0; 4];
+        self.input.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes) as usize)
+    }
+
+    fn read_str(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.read_len()?;
+        // Read through `take`, so that a corrupt length cannot allocate more
+        // than the input holds.
+        let mut bytes = Vec::new();
+        (&mut self.input).take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() == len {
+            Ok(bytes)
+        } else {
+            Err(io::ErrorKind::UnexpectedEof.into())
+        }
+    }
+}
+
+impl<R: Read> Iterator for ColumnarReader<R> {
+    type Item = Result<RowGroup, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_group() {
+            Ok(Some(group)) => Some(Ok(group)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(export_error(err)))
+            }
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
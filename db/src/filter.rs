@@ -0,0 +1,369 @@
+//! Structured filters over albums and items, which can be matched in memory
+//! or translated into a parameterized SQL query.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+#[cfg(feature = "sqlite")]
+use std::fmt::Write;
+use std::ops::Not;
+
+#[cfg(feature = "sqlite")]
+use rusqlite::types::Value as SqlValue;
+
+use super::Value;
+
+/// An album or item, whose fields and flexible attributes can be filtered
+/// on by name.
+pub trait Entity {
+    /// The table that holds entities of this kind.
+    const TABLE: &'static str;
+    /// The table that holds their flexible attributes.
+    const ATTR_TABLE: &'static str;
+    /// The names of the fields.
+    const FIELDS: &'static [&'static str];
+
+    /// The value of the field or flexible attribute `name`, if there is one.
+    fn get(&self, name: &str) -> Option<Value>;
+
+    /// The flexible attributes.
+    fn flex(&self) -> &BTreeMap<String, String>;
+
+    /// The SQL literal for the value that the field `name` takes when its
+    /// column is `NULL` or missing, or `None` if it is not a field.
+    fn column_default(name: &str) -> Option<&'static str>;
+}
+
+/// A condition on the fields or flexible attributes of an entity.
+///
+/// Fields are compared as `SQLite` would compare them: a missing flexible
+/// attribute is `NULL`, which satisfies no condition but [`is`] `NULL`.
+///
+/// [`is`]: #method.is
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// The field is `NULL`, or has the same text as the value.
+    Is { field: String, value: Value },
+    /// The text of the field contains `text`, ignoring ASCII case.
+    Contains { field: String, text: String },
+    /// The field is a number between `min` and `max`, inclusive.
+    Range {
+        field: String,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// The condition does not hold.
+    Not(Box<Condition>),
+    /// Every one of the conditions holds.
+    All(Vec<Condition>),
+    /// At least one of the conditions holds.
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    /// The field `field` has the same text as `value`, or is `NULL` if
+    /// `value` is.
+    pub fn is<V: Into<Value>>(field: &str, value: V) -> Self {
+        Condition::Is {
+            field: field.to_string(),
+            value: value.into(),
+        }
+    }
+
+    /// The text of the field `field` contains `text`, ignoring ASCII case.
+    #[must_use]
+    pub fn contains(field: &str, text: &str) -> Self {
+        Condition::Contains {
+            field: field.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// The field `field` is a number between `min` and `max`, inclusive.
+    #[must_use]
+    pub fn range(field: &str, min: Option<f64>, max: Option<f64>) -> Self {
+        Condition::Range {
+            field: field.to_string(),
+            min,
+            max,
+        }
+    }
+
+    /// Whether `entity` satisfies this condition.
+    pub fn matches<T: Entity>(&self, entity: &T) -> bool {
+        let get = |field: &str| entity.get(field).unwrap_or(Value::Null);
+
+        match self {
+            Condition::Is { field, value } => match (get(field), value.as_text()) {
+                (Value::Null, None) => true,
+                (actual, Some(expected)) => actual.as_text().is_some_and(|a| a == expected),
+                (_, None) => false,
+            },
+            Condition::Contains { field, text } => get(field).as_text().is_some_and(|actual| {
+                actual
+                    .to_ascii_lowercase()
+                    .contains(&text.to_ascii_lowercase())
+            }),
+            Condition::Range { field, min, max } => get(field)
+                .as_real()
+                .is_some_and(|n| min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max)),
+            Condition::Not(condition) => !condition.matches(entity),
+            Condition::All(conditions) => conditions.iter().all(|c| c.matches(entity)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.matches(entity)),
+        }
+    }
+}
+
+impl Not for Condition {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Condition::Not(Box::new(self))
+    }
+}
+
+/// A field to sort by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sort {
+    pub field: String,
+    pub ascending: bool,
+}
+
+/// Conditions that entities must all satisfy, and how to sort and page
+/// through the ones that do.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    pub conditions: Vec<Condition>,
+    /// Fields to sort by, in order of priority. Ties are broken by id.
+    pub sort: Vec<Sort>,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+impl Filter {
+    /// A filter that every entity passes, in order of id.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also require `condition` to hold.
+    #[must_use]
+    pub fn with(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Sort by `field`, after any fields that were given before it.
+    #[must_use]
+    pub fn sort_by(mut self, field: &str, ascending: bool) -> Self {
+        self.sort.push(Sort {
+            field: field.to_string(),
+            ascending,
+        });
+        self
+    }
+
+    /// Return at most `limit` entities.
+    #[must_use]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` entities.
+    #[must_use]
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Whether `entity` satisfies all of the conditions.
+    pub fn matches<T: Entity>(&self, entity: &T) -> bool {
+        self.conditions.iter().all(|c| c.matches(entity))
+    }
+
+    /// Filter, sort and page through `entities` in memory, with the same
+    /// result as querying the database for them.
+    #[must_use]
+    pub fn apply<T: Entity>(&self, entities: Vec<T>) -> Vec<T> {
+        let mut entities = entities
+            .into_iter()
+            .filter(|entity| self.matches(entity))
+            .map(|entity| {
+                let mut keys = self
+                    .sort
+                    .iter()
+                    .map(|sort| entity.get(&sort.field).unwrap_or(Value::Null))
+                    .collect::<Vec<_>>();
+                keys.push(entity.get("id").unwrap_or(Value::Null));
+                (keys, entity)
+            })
+            .collect::<Vec<_>>();
+
+        entities.sort_by(|(a, _), (b, _)| {
+            a.iter()
+                .zip(b)
+                .zip(
+                    self.sort
+                        .iter()
+                        .map(|sort| sort.ascending)
+                        .chain(Some(true)),
+                )
+                .map(|((a, b), ascending)| {
+                    let order = a.sql_cmp(b);
+                    if ascending {
+                        order
+                    } else {
+                        order.reverse()
+                    }
+                })
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        entities
+            .into_iter()
+            .map(|(_, entity)| entity)
+            .skip(self.offset as usize)
+            .take(self.limit.map_or(usize::MAX, |limit| limit as usize))
+            .collect()
+    }
+
+    /// The clause to follow `SELECT ... FROM <table>` with, and its
+    /// parameters. `columns` are the columns that the table actually has.
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn to_sql<T: Entity>(&self, columns: &[String]) -> (String, Vec<SqlValue>) {
+        let mut sql = Sql::<T> {
+            columns,
+            flex: Vec::new(),
+            params: Vec::new(),
+            entities: std::marker::PhantomData,
+        };
+
+        let conditions = self
+            .conditions
+            .iter()
+            .map(|c| sql.condition(c))
+            .collect::<Vec<_>>();
+        let mut order = String::new();
+        for sort in &self.sort {
+            let dir = if sort.ascending { "ASC" } else { "DESC" };
+            let _ = write!(order, "{} {dir}, ", sql.column(&sort.field));
+        }
+
+        // The joins come first in the query, so their parameters do too.
+        let mut params = sql
+            .flex
+            .iter()
+            .map(|key| SqlValue::Text(key.clone()))
+            .collect::<Vec<_>>();
+        params.append(&mut sql.params);
+
+        let table = T::TABLE;
+        let mut clause = String::new();
+        for i in 0..sql.flex.len() {
+            let _ = write!(
+                clause,
+                "LEFT JOIN {attr} AS flex{i} ON flex{i}.entity_id = {table}.id AND flex{i}.key = ? ",
+                attr = T::ATTR_TABLE,
+            );
+        }
+        if !conditions.is_empty() {
+            let _ = write!(clause, "WHERE {} ", conditions.join(" AND "));
+        }
+        let _ = write!(clause, "ORDER BY {order}{table}.id");
+        if self.limit.is_some() || self.offset > 0 {
+            clause.push_str(" LIMIT ? OFFSET ?");
+            params.push(SqlValue::Integer(self.limit.map_or(-1, i64::from)));
+            params.push(SqlValue::Integer(self.offset.into()));
+        }
+
+        (clause, params)
+    }
+}
+
+/// The state of translating a filter into SQL.
+#[cfg(feature = "sqlite")]
+struct Sql<'a, T> {
+    columns: &'a [String],
+    /// The keys of the flexible attributes joined so far.
+    flex: Vec<String>,
+    /// The parameters of the conditions, in order.
+    params: Vec<SqlValue>,
+    entities: std::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "sqlite")]
+impl<T: Entity> Sql<'_, T> {
+    /// An expression for the value of `field`.
+    fn column(&mut self, field: &str) -> String {
+        if let Some(default) = T::column_default(field) {
+            return if self.columns.iter().any(|c| c == field) {
+                format!("IFNULL({}.\"{field}\", {default})", T::TABLE)
+            } else {
+                default.to_string()
+            };
+        }
+
+        let i = self
+            .flex
+            .iter()
+            .position(|key| key == field)
+            .unwrap_or_else(|| {
+                self.flex.push(field.to_string());
+                self.flex.len() - 1
+            });
+        format!("flex{i}.value")
+    }
+
+    /// An expression that is `1` if `condition` holds, or else `0` (never
+    /// `NULL`, so that `NOT` works as expected).
+    fn condition(&mut self, condition: &Condition) -> String {
+        match condition {
+            Condition::Is { field, value } => {
+                let column = self.column(field);
+                match value.as_text() {
+                    None => format!("({column} IS NULL)"),
+                    Some(text) => {
+                        self.params.push(SqlValue::Text(text.into_owned()));
+                        format!("IFNULL(CAST({column} AS TEXT) = ?, 0)")
+                    }
+                }
+            }
+            Condition::Contains { field, text } => {
+                let column = self.column(field);
+                let escaped = text
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                self.params.push(SqlValue::Text(format!("%{escaped}%")));
+                format!("IFNULL(CAST({column} AS TEXT) LIKE ? ESCAPE '\\', 0)")
+            }
+            Condition::Range { field, min, max } => {
+                let column = self.column(field);
+                let mut bounds = vec![format!("{column} IS NOT NULL")];
+                for (bound, op) in &[(min, ">="), (max, "<=")] {
+                    if let Some(bound) = bound {
+                        self.params.push(SqlValue::Real(*bound));
+                        bounds.push(format!("CAST({column} AS REAL) {op} ?"));
+                    }
+                }
+                format!("IFNULL({}, 0)", bounds.join(" AND "))
+            }
+            Condition::Not(condition) => format!("NOT {}", self.condition(condition)),
+            Condition::All(conditions) => self.group(conditions, " AND ", "1"),
+            Condition::Any(conditions) => self.group(conditions, " OR ", "0"),
+        }
+    }
+
+    fn group(&mut self, conditions: &[Condition], op: &str, empty: &str) -> String {
+        if conditions.is_empty() {
+            return empty.to_string();
+        }
+        let conditions = conditions
+            .iter()
+            .map(|c| self.condition(c))
+            .collect::<Vec<_>>();
+        format!("({})", conditions.join(op))
+    }
+}
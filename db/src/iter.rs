@@ -0,0 +1,113 @@
+use std::marker::PhantomData;
+use std::vec;
+
+use rusqlite::Connection;
+
+use super::Error;
+
+/// How many entries [`Iter`] reads with each query.
+pub(crate) const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+/// A table whose entries can be read a page at a time, in order of id.
+pub trait Paged: Sized {
+    /// The id of this entry.
+    fn id(&self) -> u32;
+
+    /// Bind up to `limit` entries whose id is greater than `after`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the table cannot be read.
+    fn read_page(c: &Connection, after: Option<u32>, limit: u32) -> Result<Vec<Self>, Error>;
+}
+
+/// An iterator over the pages of a table.
+///
+/// Each page is read with its own query, keyed by the last id of the one
+/// before it, so only a single page is held in memory at a time.
+pub struct Pages<'c, T> {
+    conn: &'c Connection,
+    after: Option<u32>,
+    size: u32,
+    done: bool,
+    entries: PhantomData<T>,
+}
+
+impl<'c, T: Paged> Pages<'c, T> {
+    /// Read pages of up to `size` entries, starting from the lowest id.
+    #[must_use]
+    pub fn new(conn: &'c Connection, size: u32) -> Self {
+        Self {
+            conn,
+            after: None,
+            size: size.max(1),
+            done: false,
+            entries: PhantomData,
+        }
+    }
+
+    /// Skip the entries with an id of `id` or lower.
+    #[must_use]
+    pub fn after(mut self, id: u32) -> Self {
+        self.after = Some(id);
+        self
+    }
+}
+
+impl<T: Paged> Iterator for Pages<'_, T> {
+    type Item = Result<Vec<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match T::read_page(self.conn, self.after, self.size) {
+            Ok(page) => {
+                self.done = page.len() < self.size as usize;
+                self.after = Some(page.last()?.id());
+                Some(Ok(page))
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// An iterator over the entries of a table, in order of id.
+///
+/// Entries are read a page at a time; see [`Pages`].
+pub struct Iter<'c, T> {
+    pages: Pages<'c, T>,
+    page: vec::IntoIter<T>,
+}
+
+impl<'c, T: Paged> Iter<'c, T> {
+    /// Iterate over the entries in each of `pages`.
+    #[must_use]
+    pub fn new(pages: Pages<'c, T>) -> Self {
+        Self {
+            pages,
+            page: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<T: Paged> Iterator for Iter<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(Ok(entry));
+            }
+
+            match self.pages.next()? {
+                Ok(page) => self.page = page.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
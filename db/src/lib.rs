@@ -2,98 +2,520 @@
 
 #![deny(clippy::pedantic)]
 
+#[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde_derive;
 
+use std::borrow::Cow;
+#[cfg(feature = "sqlite")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "sqlite")]
+use std::path::Path;
 use std::path::PathBuf;
 
-#[cfg(not(target_arch = "wasm32"))]
-pub use rusqlite::Error;
-#[cfg(not(target_arch = "wasm32"))]
-use rusqlite::{Connection, OpenFlags};
+#[cfg(feature = "sqlite")]
+use rusqlite::{
+    types::{FromSql, ToSql, ValueRef},
+    Connection, OpenFlags, Row, RowIndex, Statement, NO_PARAMS,
+};
 
+pub mod backend;
+pub mod check;
+#[cfg(feature = "sqlite")]
+pub mod config;
+pub mod duplicates;
+mod error;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod filter;
+#[cfg(feature = "sqlite")]
+mod iter;
+mod library;
+pub mod local_time;
+pub mod multi;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod path;
+pub mod stats;
+pub mod template;
 mod tests;
+mod values;
+#[cfg(all(feature = "watch", target_os = "linux"))]
+pub mod watch;
+#[cfg(feature = "sqlite")]
+mod write;
+
+pub use backend::{Backend, Memory};
+pub use error::Error;
+use filter::Entity;
+#[cfg(feature = "sqlite")]
+use filter::Filter;
+#[cfg(feature = "sqlite")]
+use iter::DEFAULT_PAGE_SIZE;
+#[cfg(feature = "sqlite")]
+pub use iter::{Iter, Paged, Pages};
+pub use library::{AlbumRef, ItemRef, Library};
+use values::ToValue;
+pub use values::{PartialDate, Value};
+#[cfg(feature = "sqlite")]
+pub use write::{Changes, Writer};
 
 macro_rules! def_sqlite_struct {
     ( $(#[$outer:meta])* $name:ident [ $( $(#[$inner:meta])* $field:ident: $typ:ty $(; $func:ident)?, )* ]
     ) => {
         $(#[$outer])*
-        #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+        #[derive(Clone, Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
         pub struct $name {
             $( $(#[$inner])* pub $field: $typ ),*
         }
 
-        #[cfg(not(target_arch = "wasm32"))]
+        def_sqlite_struct! {
+            @from_row $name [ $( $field: $typ $(; $func)?, )* ]
+        }
+
+        #[cfg(feature = "sqlite")]
+        impl $name {
+            #[doc = "Bind the metadata for a single entry of `table`."]
+            #[doc = ""]
+            #[doc = "Columns are looked up by name, so their order does not matter."]
+            #[doc = "Any field whose column is missing or `NULL` takes the default"]
+            #[doc = "value for its type."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Fails if a value cannot be converted to the type of its field."]
+            pub fn from_row(db_row__: &::rusqlite::Row, table: &'static str) -> Result<Self, Error> {
+                Self::bind_row(db_row__, table, &Self::named_columns())
+            }
+        }
+    };
+
+    ( $(#[$outer:meta])* $name:ident $table:ident $attr_table:ident
+      [ $( $(#[$inner:meta])* $field:ident: $typ:ty $(; $func:ident)?, )* ]
+    ) => {
+        $(#[$outer])*
+        #[derive(Clone, Debug, Default, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+        pub struct $name {
+            $( $(#[$inner])* pub $field: $typ, )*
+            #[doc = "Flexible attributes from the `"]
+            #[doc = stringify!($attr_table)]
+            #[doc = "` table, e.g. plugin or user-defined fields."]
+            #[cfg_attr(feature = "serde", serde(flatten))]
+            pub flex: ::std::collections::BTreeMap<String, String>,
+            /// Columns of the table that are not known to this crate, e.g.
+            /// fields added by a newer version of beets.
+            #[cfg_attr(feature = "serde", serde(skip_serializing_if = "::std::collections::BTreeMap::is_empty", default))]
+            pub extra: ::std::collections::BTreeMap<String, String>,
+        }
+
+        def_sqlite_struct! {
+            @from_row $name [ $( $field: $typ $(; $func)?, )* ]
+            flex: ::std::collections::BTreeMap::new(),
+            extra: ::std::collections::BTreeMap::new(),
+        }
+
+        def_sqlite_struct! {
+            $name stringify!($table), stringify!($attr_table)
+        }
+    };
+
+    ( @from_row $name:ident [ $( $field:ident: $typ:ty $(; $func:ident)?, )* ]
+      $( $extra:ident: $init:expr, )*
+    ) => {
+        impl $name {
+            /// The names of the columns that are bound to fields.
+            pub const FIELDS: &'static [&'static str] = &[ $( stringify!($field) ),* ];
+
+            /// The value of the field `name`, if there is one.
+            #[allow(dead_code)]
+            fn get_field(&self, name__: &str) -> Option<Value> {
+                match name__ {
+                    $( stringify!($field) => Some(ToValue::to_value(&self.$field)), )*
+                    _ => None,
+                }
+            }
+
+            /// The SQL literal for the value that the field `name` takes when
+            /// its column is `NULL` or missing.
+            #[allow(dead_code)]
+            fn field_default(name__: &str) -> Option<&'static str> {
+                match name__ {
+                    $( stringify!($field) => Some(<$typ as ToValue>::SQL_DEFAULT), )*
+                    _ => None,
+                }
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
         impl $name {
-            #[allow(unused_assignments)]
-            /// Bind the metadata for a single entry.
-            pub fn from_row(db_row__: &::rusqlite::Row) -> Self {
-                let mut field_idx__ = 0;
+            /// The position of the column for each of the fields in the rows
+            /// of `stmt`, so that reading a row does not have to look every
+            /// column up by name.
+            fn column_indices(stmt: &::rusqlite::Statement) -> [Option<usize>; $name::FIELDS.len()] {
+                let names = stmt.column_names();
+                let mut indices = [None; $name::FIELDS.len()];
+                for (index, field) in indices.iter_mut().zip(Self::FIELDS) {
+                    *index = names.iter().position(|name| name.eq_ignore_ascii_case(field));
+                }
+                indices
+            }
+
+            /// The name of the column for each of the fields, for a row
+            /// without a statement to find their positions in.
+            fn named_columns() -> [Option<&'static str>; $name::FIELDS.len()] {
+                let mut names = [None; $name::FIELDS.len()];
+                for (name, field) in names.iter_mut().zip(Self::FIELDS) {
+                    *name = Some(*field);
+                }
+                names
+            }
 
+            /// Bind a row with the column for each field at `columns__`. A
+            /// field without a column takes its default value.
+            fn bind_row<I: ::rusqlite::RowIndex + Copy>(
+                db_row__: &::rusqlite::Row,
+                table__: &'static str,
+                columns__: &[Option<I>; $name::FIELDS.len()],
+            ) -> Result<Self, Error> {
+                let mut columns__ = columns__.iter().copied();
                 $(
-                    let $field = def_field!(db_row__.get(field_idx__) $(, $func)?);
-                    field_idx__ += 1;
+                    let $field = def_field!(
+                        get_column(db_row__, table__, stringify!($field), columns__.next().flatten())?
+                        $(, $func)?
+                    );
                 )*
 
-                Self {
-                    $( $field ),*
-                }
+                Ok(Self {
+                    $( $field, )*
+                    $( $extra: $init, )*
+                })
             }
         }
     };
 
-    ( $(#[$outer:meta])* $name:ident $table:ident $fields:tt ) => {
-        def_sqlite_struct! {
-            $(#[$outer])*
-            $name $fields
+    ( $name:ident $table:expr, $attr_table:expr ) => {
+        impl $name {
+            /// The value of the field or flexible attribute `name`, if there
+            /// is one.
+            #[must_use]
+            pub fn get(&self, name: &str) -> Option<Value> {
+                self.get_field(name)
+                    .or_else(|| self.flex.get(name).map(|value| Value::Text(value.clone())))
+            }
         }
 
-        def_sqlite_struct!{
-            $name stringify!($table)
+        impl Entity for $name {
+            const TABLE: &'static str = $table;
+            const ATTR_TABLE: &'static str = $attr_table;
+            const FIELDS: &'static [&'static str] = Self::FIELDS;
+
+            fn get(&self, name: &str) -> Option<Value> {
+                self.get(name)
+            }
+
+            fn flex(&self) -> &::std::collections::BTreeMap<String, String> {
+                &self.flex
+            }
+
+            fn column_default(name: &str) -> Option<&'static str> {
+                Self::field_default(name)
+            }
         }
-    };
 
-    ( $name:ident $table:expr ) => {
-        #[cfg(not(target_arch = "wasm32"))]
+        #[cfg(feature = "sqlite")]
         impl $name {
+            #[doc = "Bind the metadata for a single entry of the `"]
+            #[doc = $table]
+            #[doc = "` table."]
+            #[doc = ""]
+            #[doc = "Columns are looked up by name, so their order does not matter."]
+            #[doc = "Any field whose column is missing or `NULL` takes the default"]
+            #[doc = "value for its type."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Fails if a value cannot be converted to the type of its field."]
+            pub fn from_row(db_row__: &::rusqlite::Row) -> Result<Self, Error> {
+                Self::bind_row(db_row__, $table, &Self::named_columns())
+            }
+
             #[doc = "Bind each of the entries in the `"]
             #[doc = $table]
+            #[doc = "` table, along with their flexible attributes from the `"]
+            #[doc = $attr_table]
             #[doc = "` table."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Fails if either table cannot be queried, lacks an `id` column,"]
+            #[doc = "or holds a value that cannot be converted to its field's type."]
             pub fn read_all(c: &::rusqlite::Connection) ->
-                ::std::result::Result<::std::vec::Vec<Self>, ::rusqlite::Error>
+                ::std::result::Result<::std::vec::Vec<Self>, Error>
+            {
+                Self::select(c, "", ::rusqlite::NO_PARAMS)
+            }
+
+            #[doc = "Bind up to `limit` entries of the `"]
+            #[doc = $table]
+            #[doc = "` table whose id is greater than `after`, in order of id."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "See [`read_all`](#method.read_all)."]
+            pub fn read_page(c: &::rusqlite::Connection, after: Option<u32>, limit: u32) ->
+                ::std::result::Result<::std::vec::Vec<Self>, Error>
             {
-                let mut stmt = c.prepare(concat!("SELECT * FROM ", $table))?;
-                let rows = stmt.query_map(::rusqlite::NO_PARAMS, Self::from_row)?;
+                Self::select(
+                    c,
+                    "WHERE id > ? ORDER BY id LIMIT ?",
+                    &[&after.map_or(-1, i64::from), &i64::from(limit)],
+                )
+            }
+
+            #[doc = "Iterate over the entries of the `"]
+            #[doc = $table]
+            #[doc = "` table in order of id, reading them a page at a time."]
+            pub fn iter(c: &::rusqlite::Connection) -> Iter<'_, Self> {
+                Iter::new(Self::pages(c, DEFAULT_PAGE_SIZE))
+            }
+
+            #[doc = "Iterate over the entries of the `"]
+            #[doc = $table]
+            #[doc = "` table in order of id, in pages of up to `size` entries."]
+            pub fn pages(c: &::rusqlite::Connection, size: u32) -> Pages<'_, Self> {
+                Pages::new(c, size)
+            }
+
+            #[doc = "Read the id of every entry in the `"]
+            #[doc = $table]
+            #[doc = "` table."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Fails if the table cannot be queried."]
+            pub fn read_ids(c: &::rusqlite::Connection) ->
+                ::std::result::Result<::std::collections::BTreeSet<u32>, Error>
+            {
+                let mut stmt = c.prepare(concat!("SELECT id FROM ", $table))?;
+                let ids = stmt.query_and_then(::rusqlite::NO_PARAMS, |row| row.get_checked(0))?;
+                Ok(ids.collect::<::rusqlite::Result<_>>()?)
+            }
+
+            #[doc = "Find which of the `known` ids are no longer in the `"]
+            #[doc = $table]
+            #[doc = "` table, in ascending order."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "Fails if the table cannot be queried."]
+            pub fn read_removed<I: IntoIterator<Item = u32>>(
+                c: &::rusqlite::Connection,
+                known: I,
+            ) -> ::std::result::Result<::std::vec::Vec<u32>, Error> {
+                let current = Self::read_ids(c)?;
+                let known = known.into_iter().collect::<::std::collections::BTreeSet<_>>();
+                Ok(known.difference(&current).copied().collect())
+            }
+
+            #[doc = "Bind the entries of the `"]
+            #[doc = $table]
+            #[doc = "` table that pass `filter`, in its order, without reading"]
+            #[doc = "any others."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "See [`read_all`](#method.read_all)."]
+            pub fn read_filtered(c: &::rusqlite::Connection, filter: &Filter) ->
+                ::std::result::Result<::std::vec::Vec<Self>, Error>
+            {
+                let columns = table_columns(c, $table)?;
+                let (clause, params) = filter.to_sql::<Self>(&columns);
+                let params = params
+                    .iter()
+                    .map(|p| p as &dyn ::rusqlite::types::ToSql)
+                    .collect::<::std::vec::Vec<_>>();
+                Self::select(c, &clause, &params)
+            }
+
+            /// Bind the entries selected by `clause`, which follows the
+            /// table name in the query, along with their flexible attributes.
+            fn select(
+                c: &::rusqlite::Connection,
+                clause: &str,
+                params: &[&dyn ::rusqlite::types::ToSql],
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, Error> {
+                let mut stmt = c.prepare(&format!(
+                    concat!("SELECT ", $table, ".* FROM ", $table, " {}"),
+                    clause,
+                ))?;
+                require_columns(&stmt, $table, &["id"])?;
+
+                let mut flex = Attribute::read_grouped(
+                    c,
+                    $attr_table,
+                    &format!(
+                        concat!("WHERE entity_id IN (SELECT ", $table, ".id FROM ", $table, " {})"),
+                        clause,
+                    ),
+                    params,
+                )?;
+
+                let columns = Self::column_indices(&stmt);
+                let extra_columns = unknown_columns(&stmt, Self::FIELDS);
+                let rows = stmt.query_and_then(params, |row| {
+                    let mut entry = Self::bind_row(row, $table, &columns)?;
+                    entry.extra = read_columns(row, &extra_columns);
+                    Ok::<_, Error>(entry)
+                })?;
 
                 let mut v = ::std::vec::Vec::new();
                 for row in rows {
-                    v.push(row?);
+                    let mut entry = row?;
+                    if let Some(attrs) = flex.remove(&entry.id) {
+                        entry.flex = attrs;
+                    }
+                    v.push(entry);
                 }
 
                 Ok(v)
             }
+
+            /// Apply `changes` to the entry `id` and read it back. The caller
+            /// is left to run this in a transaction.
+            fn update(
+                c: &::rusqlite::Connection,
+                id: u32,
+                changes: &Changes,
+            ) -> ::std::result::Result<Self, Error> {
+                changes.apply(c, $table, $attr_table, id)?;
+                Self::select(c, "WHERE id = ?", &[&id])?
+                    .pop()
+                    .ok_or(Error::NoSuchEntry { table: $table, id })
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        impl Paged for $name {
+            fn id(&self) -> u32 {
+                self.id
+            }
+
+            fn read_page(c: &::rusqlite::Connection, after: Option<u32>, limit: u32) ->
+                ::std::result::Result<::std::vec::Vec<Self>, Error>
+            {
+                Self::read_page(c, after, limit)
+            }
         }
     };
 }
 
+#[cfg(feature = "sqlite")]
 macro_rules! def_field {
     ( $defn:expr, $func:ident ) => {
-        $func($defn)
+        $defn.map($func).unwrap_or_default()
     };
     ( $defn:expr ) => {
-        $defn
+        $defn.unwrap_or_default()
     };
 }
 
-#[allow(clippy::needless_pass_by_value)]
+/// Get the value of the column for the field `column` at `index`, or `None`
+/// if it is `NULL` or not present.
+#[cfg(feature = "sqlite")]
+fn get_column<T: FromSql, I: RowIndex>(
+    row: &Row,
+    table: &'static str,
+    column: &'static str,
+    index: Option<I>,
+) -> Result<Option<T>, Error> {
+    let Some(index) = index else {
+        return Ok(None);
+    };
+    match row.get_checked(index) {
+        Ok(value) => Ok(value),
+        Err(rusqlite::Error::InvalidColumnName(_)) => Ok(None),
+        Err(source) => Err(Error::BadValue {
+            table,
+            row: row.get_checked("id").unwrap_or_default(),
+            column,
+            source,
+        }),
+    }
+}
+
+/// The names of the columns that `table` has in the database, which may
+/// differ from the fields of its struct.
+#[cfg(feature = "sqlite")]
+fn table_columns(c: &Connection, table: &str) -> Result<Vec<String>, Error> {
+    Ok(c.prepare(&format!("SELECT * FROM {table} LIMIT 0"))?
+        .column_names()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+/// Make sure that `stmt` returns each of the `required` columns.
+#[cfg(feature = "sqlite")]
+fn require_columns(
+    stmt: &Statement,
+    table: &'static str,
+    required: &[&'static str],
+) -> Result<(), Error> {
+    let columns = stmt.column_names();
+    match required.iter().find(|column| !columns.contains(column)) {
+        Some(column) => Err(Error::UnsupportedSchema { table, column }),
+        None => Ok(()),
+    }
+}
+
+/// Find the columns returned by `stmt` that are not in `known`.
+#[cfg(feature = "sqlite")]
+fn unknown_columns(stmt: &Statement, known: &[&str]) -> Vec<(usize, String)> {
+    stmt.column_names()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, name)| !known.contains(name))
+        .map(|(idx, name)| (idx, name.to_string()))
+        .collect()
+}
+
+/// Read the given columns as strings, skipping any that are `NULL`.
+#[cfg(feature = "sqlite")]
+fn read_columns(row: &Row, columns: &[(usize, String)]) -> BTreeMap<String, String> {
+    columns
+        .iter()
+        .filter_map(|(idx, name)| {
+            let value = match row.get_raw(*idx) {
+                ValueRef::Null => return None,
+                ValueRef::Integer(i) => i.to_string(),
+                ValueRef::Real(f) => f.to_string(),
+                ValueRef::Text(t) => t.to_string(),
+                ValueRef::Blob(b) => String::from_utf8_lossy(b).into_owned(),
+            };
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+#[cfg(feature = "sqlite")]
 fn blob_to_path(v: Vec<u8>) -> PathBuf {
-    String::from(String::from_utf8_lossy(&v)).into()
+    path::from_bytes(v)
 }
 
+#[cfg(feature = "sqlite")]
+#[allow(clippy::needless_pass_by_value)]
+fn split_multi(v: String) -> Vec<String> {
+    multi::split(&v)
+}
+
+#[cfg(feature = "sqlite")]
 fn optional_blob_to_path(v: Option<Vec<u8>>) -> Option<PathBuf> {
     v.map(blob_to_path)
 }
 
+#[cfg(feature = "serde")]
 fn is_num_zero<T: Default + PartialEq>(n: &T) -> bool {
     n == &T::default()
 }
@@ -108,207 +530,388 @@ def_sqlite_struct! {
     ]
 }
 
+#[cfg(feature = "sqlite")]
+impl Attribute {
+    /// Bind the entries of an attribute table selected by `clause`, grouped
+    /// by the id of the album or item they belong to.
+    fn read_grouped(
+        c: &Connection,
+        table: &'static str,
+        clause: &str,
+        params: &[&dyn ToSql],
+    ) -> Result<HashMap<u32, BTreeMap<String, String>>, Error> {
+        let mut stmt = c.prepare(&format!("SELECT * FROM {table} {clause}"))?;
+        require_columns(&stmt, table, &["entity_id", "key", "value"])?;
+        let columns = Self::column_indices(&stmt);
+        let rows = stmt.query_and_then(params, |row| Self::bind_row(row, table, &columns))?;
+
+        let mut grouped = HashMap::<_, BTreeMap<_, _>>::new();
+        for row in rows {
+            let Self {
+                entity_id,
+                key,
+                value,
+                ..
+            } = row?;
+            grouped.entry(entity_id).or_default().insert(key, value);
+        }
+
+        Ok(grouped)
+    }
+}
+
 def_sqlite_struct! {
     /// All of the fields that an album has in the beets schema.
-    Album albums [
+    Album albums album_attributes [
         id: u32,
-        /// This is converted lossily - any invalid UTF-8 will be
-        /// [transcribed as the replacement character.](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy)
+        /// The exact bytes stored by beets, see [`path`](path/index.html).
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_optional_path", default))]
         artpath: Option<PathBuf>; optional_blob_to_path,
-        #[serde(skip)]
+        #[cfg_attr(feature = "serde", serde(skip))]
         added: f64,
         albumartist: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumartist_sort: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumartist_credit: String,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists_sort: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists_credit: Vec<String>; split_multi,
         album: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         genre: String,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        genres: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         year: u16,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         month: u8,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         day: u8,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         disctotal: u32,
         comp: bool,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_albumid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_albumartistid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        mb_albumartistids: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumtype: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         label: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_releasegroupid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         asin: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         catalognum: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         script: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         language: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         country: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumstatus: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumdisambig: String,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_album_gain: Option<f64>,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_album_peak: Option<f64>,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
-        r128_album_gain: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        /// A Q7.8 fixed-point number of decibels, which can be negative.
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
+        r128_album_gain: i32,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_year: u16,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_month: u8,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_day: u8,
  ]
 }
 
 def_sqlite_struct! {
     /// All of the fields that an "item" (track) has in the beets schema.
-    Item items [
+    Item items item_attributes [
         id: u32,
-        /// This is converted lossily - any invalid UTF-8 will be
-        /// [transcribed as the replacement character.](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy)
+        /// The exact bytes stored by beets, see [`path`](path/index.html).
+        #[cfg_attr(feature = "serde", serde(with = "path::serde_path"))]
         path: PathBuf; blob_to_path,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         album_id: Option<u32>,
         title: String,
         artist: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         artist_sort: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         artist_credit: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        artists: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        artists_sort: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        artists_credit: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         album: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumartist: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumartist_sort: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumartist_credit: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists_sort: Vec<String>; split_multi,
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        albumartists_credit: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         genre: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        genres: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         lyricist: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         composer: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         composer_sort: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         arranger: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         grouping: String,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         year: u16,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         month: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         day: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         track: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         tracktotal: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         disc: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         disctotal: u32,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         lyrics: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         comments: String,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         bpm: u32,
         comp: bool,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_trackid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_albumid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_artistid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        mb_artistids: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_albumartistid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        /// Multi-valued, see [`multi`](multi/index.html).
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty", default))]
+        mb_albumartistids: Vec<String>; split_multi,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_releasetrackid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumtype: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         label: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         acoustid_fingerprint: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         acoustid_id: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         mb_releasegroupid: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         asin: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         catalognum: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         script: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         language: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         country: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumstatus: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         media: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         albumdisambig: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         disctitle: String,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         encoder: String,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_track_gain: Option<f64>,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_track_peak: Option<f64>,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_album_gain: Option<f64>,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         rg_album_peak: Option<f64>,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
-        r128_track_gain: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
-        r128_album_gain: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        /// A Q7.8 fixed-point number of decibels, which can be negative.
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
+        r128_track_gain: i32,
+        /// A Q7.8 fixed-point number of decibels, which can be negative.
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
+        r128_album_gain: i32,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_year: u16,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_month: u8,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         original_day: u8,
-        #[serde(skip_serializing_if = "Option::is_none", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
         initial_key: Option<String>,
         length: f64,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         bitrate: u32,
-        #[serde(skip_serializing_if = "String::is_empty", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty", default))]
         format: String,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         samplerate: u32,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         bitdepth: u16,
-        #[serde(skip_serializing_if = "is_num_zero", default)]
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_num_zero", default))]
         channels: u8,
-        #[serde(skip, default)]
+        #[cfg_attr(feature = "serde", serde(skip, default))]
         mtime: f64,
-        #[serde(skip, default)]
+        #[cfg_attr(feature = "serde", serde(skip, default))]
         added: f64,
     ]
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+impl Album {
+    /// The path to the album art, for display. Any invalid UTF-8 is
+    /// [transcribed as the replacement character.](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy)
+    #[must_use]
+    pub fn artpath_display(&self) -> Option<Cow<'_, str>> {
+        self.artpath.as_ref().map(|path| path.to_string_lossy())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Album {
+    /// Bind the albums that were added after `since`, in seconds since the
+    /// Unix epoch, or that have an item which changed after then (see
+    /// [`Item::read_changed_since`]).
+    ///
+    /// # Errors
+    ///
+    /// See [`Album::read_all`].
+    pub fn read_changed_since(c: &Connection, since: f64) -> Result<Vec<Self>, Error> {
+        Self::select(
+            c,
+            "WHERE added > ?1 OR id IN \
+             (SELECT album_id FROM items WHERE mtime > ?1 OR added > ?1)",
+            &[&since],
+        )
+    }
+}
+
+impl Item {
+    /// The path to the file, for display. Any invalid UTF-8 is
+    /// [transcribed as the replacement character.](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy)
+    #[must_use]
+    pub fn path_display(&self) -> Cow<'_, str> {
+        self.path.to_string_lossy()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Item {
+    /// Bind the items that were added, or whose files were modified, after
+    /// `since`, in seconds since the Unix epoch.
+    ///
+    /// beets only updates `mtime` when it reads or writes the file, so fields
+    /// that were changed in the database alone are not picked up.
+    ///
+    /// # Errors
+    ///
+    /// See [`Item::read_all`].
+    pub fn read_changed_since(c: &Connection, since: f64) -> Result<Vec<Self>, Error> {
+        Self::select(c, "WHERE mtime > ?1 OR added > ?1", &[&since])
+    }
+}
+
+/// The tables that every beets database has.
+#[cfg(feature = "sqlite")]
+const TABLES: &[&str] = &["albums", "album_attributes", "items", "item_attributes"];
+
+/// Open the beets database at `db_path` for reading.
+///
+/// # Errors
+///
+/// Fails if there is no file at `db_path`, if it is not a beets database, or
+/// if `SQLite` cannot open it.
+#[cfg(feature = "sqlite")]
+pub fn open<P: AsRef<Path>>(db_path: P) -> Result<Connection, Error> {
+    open_with_flags(db_path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)
+}
+
+/// Open the beets database at `db_path` with the given flags, checking that
+/// it has the beets tables.
+#[cfg(feature = "sqlite")]
+fn open_with_flags(db_path: &Path, flags: OpenFlags) -> Result<Connection, Error> {
+    if !db_path.is_file() {
+        return Err(Error::NotFound(db_path.to_path_buf()));
+    }
+
+    let conn = Connection::open_with_flags(db_path, flags)?;
+    let not_beets = || Error::NotBeets(db_path.to_path_buf());
+
+    let tables = {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .map_err(|e| {
+                if Error::is_not_a_database(&e) {
+                    not_beets()
+                } else {
+                    e.into()
+                }
+            })?;
+        let names = stmt.query_map(NO_PARAMS, |row| row.get::<_, String>(0))?;
+        names.collect::<Result<Vec<_>, _>>()?
+    };
+
+    if TABLES.iter().all(|table| tables.iter().any(|t| t == table)) {
+        Ok(conn)
+    } else {
+        Err(not_beets())
+    }
+}
+
+/// Read all of the albums and items in the database at `db_path`.
+///
+/// # Errors
+///
+/// Fails if the database cannot be opened, or if any of its tables cannot be
+/// read (see [`Item::read_all`]).
+#[cfg(feature = "sqlite")]
 pub fn read_all(db_path: PathBuf) -> Result<(Vec<Album>, Vec<Item>), Error> {
-    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let conn = open(db_path)?;
     Ok((Album::read_all(&conn)?, Item::read_all(&conn)?))
 }
@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Deref;
+#[cfg(feature = "sqlite")]
+use std::path::Path;
+
+#[cfg(feature = "sqlite")]
+use rusqlite::Connection;
+
+use super::filter::Filter;
+use super::multi::Separators;
+#[cfg(feature = "sqlite")]
+use super::open;
+#[cfg(all(feature = "watch", target_os = "linux"))]
+use super::watch::Diff;
+use super::{Album, Backend, Error, Item};
+
+/// All of the albums and items in a beets library, indexed by id and linked
+/// to each other.
+#[derive(Clone, Debug, Default)]
+pub struct Library {
+    albums: BTreeMap<u32, Album>,
+    items: BTreeMap<u32, Item>,
+    /// The ids of the items on each album, in order of disc then track.
+    tracks: HashMap<u32, Vec<u32>>,
+}
+
+impl Library {
+    /// Index the given albums and items.
+    #[must_use]
+    pub fn new(albums: Vec<Album>, items: Vec<Item>) -> Self {
+        let albums = albums
+            .into_iter()
+            .map(|album| (album.id, album))
+            .collect::<BTreeMap<_, _>>();
+        let items = items
+            .into_iter()
+            .map(|item| (item.id, item))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut library = Self {
+            albums,
+            items,
+            tracks: HashMap::new(),
+        };
+        library.index_tracks();
+        library
+    }
+
+    /// Bring the library up to date with a change to the database.
+    #[cfg(all(feature = "watch", target_os = "linux"))]
+    pub fn apply(&mut self, diff: &Diff) {
+        for id in &diff.albums.removed {
+            self.albums.remove(id);
+        }
+        for album in diff.albums.added.iter().chain(&diff.albums.modified) {
+            self.albums.insert(album.id, album.clone());
+        }
+        for id in &diff.items.removed {
+            self.items.remove(id);
+        }
+        for item in diff.items.added.iter().chain(&diff.items.modified) {
+            self.items.insert(item.id, item.clone());
+        }
+        self.index_tracks();
+    }
+
+    fn index_tracks(&mut self) {
+        let items = &self.items;
+        let mut tracks = HashMap::<_, Vec<_>>::new();
+        for item in items.values() {
+            if let Some(album_id) = item.album_id {
+                tracks.entry(album_id).or_default().push(item.id);
+            }
+        }
+        for ids in tracks.values_mut() {
+            ids.sort_by_key(|id| {
+                let item = &items[id];
+                (item.disc, item.track, item.id)
+            });
+        }
+        self.tracks = tracks;
+    }
+
+    /// Read all of the albums and items from `backend`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend cannot read them.
+    pub fn load<B: Backend>(backend: &B) -> Result<Self, Error> {
+        let all = Filter::new();
+        Ok(Self::new(backend.albums(&all)?, backend.items(&all)?))
+    }
+
+    /// Read all of the albums and items through `c`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Item::read_all`](struct.Item.html#method.read_all).
+    #[cfg(feature = "sqlite")]
+    pub fn read(c: &Connection) -> Result<Self, Error> {
+        Ok(Self::new(Album::read_all(c)?, Item::read_all(c)?))
+    }
+
+    /// Read all of the albums and items in the database at `db_path`.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_all`](fn.read_all.html).
+    #[cfg(feature = "sqlite")]
+    pub fn open<P: AsRef<Path>>(db_path: P) -> Result<Self, Error> {
+        Self::read(&open(db_path)?)
+    }
+
+    /// The album with the given id.
+    #[must_use]
+    pub fn album(&self, id: u32) -> Option<AlbumRef<'_>> {
+        self.albums.get(&id).map(|album| AlbumRef {
+            library: self,
+            album,
+        })
+    }
+
+    /// The item with the given id.
+    #[must_use]
+    pub fn item(&self, id: u32) -> Option<ItemRef<'_>> {
+        self.items.get(&id).map(|item| ItemRef {
+            library: self,
+            item,
+        })
+    }
+
+    /// All of the albums, in order of id.
+    pub fn albums(&self) -> impl Iterator<Item = AlbumRef<'_>> {
+        self.albums.values().map(move |album| AlbumRef {
+            library: self,
+            album,
+        })
+    }
+
+    /// All of the items, in order of id.
+    pub fn items(&self) -> impl Iterator<Item = ItemRef<'_>> {
+        self.items.values().map(move |item| ItemRef {
+            library: self,
+            item,
+        })
+    }
+
+    /// The items that do not belong to an album, in order of id.
+    pub fn singletons(&self) -> impl Iterator<Item = ItemRef<'_>> {
+        self.items().filter(|item| item.is_singleton())
+    }
+
+    /// Every artist credited on an item, in order of name.
+    #[must_use]
+    pub fn artists(&self, separators: &Separators) -> BTreeSet<String> {
+        self.items
+            .values()
+            .flat_map(|item| item.all_artists(separators))
+            .collect()
+    }
+
+    /// The items that credit `artist`, in order of id.
+    pub fn items_by_artist<'l>(
+        &'l self,
+        artist: &'l str,
+        separators: &'l Separators,
+    ) -> impl Iterator<Item = ItemRef<'l>> {
+        self.items()
+            .filter(move |item| item.all_artists(separators).iter().any(|a| a == artist))
+    }
+
+    /// How many albums there are.
+    #[must_use]
+    pub fn album_count(&self) -> usize {
+        self.albums.len()
+    }
+
+    /// How many items there are.
+    #[must_use]
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// An album in a [`Library`](struct.Library.html), which can find its items.
+#[derive(Clone, Copy, Debug)]
+pub struct AlbumRef<'l> {
+    library: &'l Library,
+    album: &'l Album,
+}
+
+impl<'l> AlbumRef<'l> {
+    /// The album itself, borrowed for as long as the library.
+    #[must_use]
+    pub fn as_album(&self) -> &'l Album {
+        self.album
+    }
+
+    /// The library this album is in.
+    #[must_use]
+    pub fn library(&self) -> &'l Library {
+        self.library
+    }
+
+    /// The items on this album, in order of disc then track.
+    pub fn items(&self) -> impl Iterator<Item = ItemRef<'l>> {
+        let library = self.library;
+        library
+            .tracks
+            .get(&self.album.id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |id| library.item(*id))
+    }
+}
+
+impl Deref for AlbumRef<'_> {
+    type Target = Album;
+
+    fn deref(&self) -> &Album {
+        self.album
+    }
+}
+
+/// An item in a [`Library`](struct.Library.html), which can find its album.
+#[derive(Clone, Copy, Debug)]
+pub struct ItemRef<'l> {
+    library: &'l Library,
+    item: &'l Item,
+}
+
+impl<'l> ItemRef<'l> {
+    /// The item itself, borrowed for as long as the library.
+    #[must_use]
+    pub fn as_item(&self) -> &'l Item {
+        self.item
+    }
+
+    /// The library this item is in.
+    #[must_use]
+    pub fn library(&self) -> &'l Library {
+        self.library
+    }
+
+    /// The album this item belongs to, if it is not a singleton and its album
+    /// is in the library.
+    #[must_use]
+    pub fn album(&self) -> Option<AlbumRef<'l>> {
+        self.item.album_id.and_then(|id| self.library.album(id))
+    }
+}
+
+impl Deref for ItemRef<'_> {
+    type Target = Item;
+
+    fn deref(&self) -> &Item {
+        self.item
+    }
+}
+
+impl Item {
+    /// Whether this item is a singleton, i.e. does not belong to an album.
+    #[must_use]
+    pub fn is_singleton(&self) -> bool {
+        self.album_id.is_none()
+    }
+}
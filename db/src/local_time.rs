@@ -0,0 +1,33 @@
+//! The system's time zone, which beets shows and reads dates in.
+
+#[cfg(unix)]
+use std::convert::TryFrom;
+
+/// How many seconds the system's time zone is ahead of UTC at `utc`, in
+/// seconds since the Unix epoch, or 0 where it cannot be read.
+#[cfg(unix)]
+// `tm_gmtoff` is a `c_long`, which is only 32 bits on some targets.
+#[allow(clippy::useless_conversion)]
+#[must_use]
+pub fn utc_offset(utc: i64) -> i64 {
+    let Some(time) = libc::time_t::try_from(utc).ok() else {
+        return 0;
+    };
+    // Safe, since `tm` is only read once `localtime_r` has filled it in.
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        if libc::localtime_r(std::ptr::addr_of!(time), std::ptr::addr_of_mut!(tm)).is_null() {
+            0
+        } else {
+            i64::from(tm.tm_gmtoff)
+        }
+    }
+}
+
+/// How many seconds the system's time zone is ahead of UTC at `utc`, which
+/// is taken to be UTC itself where there is no `localtime_r`.
+#[cfg(not(unix))]
+#[must_use]
+pub fn utc_offset(_: i64) -> i64 {
+    0
+}
@@ -0,0 +1,137 @@
+//! Multi-valued fields, such as `artists` and `genres`.
+//!
+//! beets 2 stores each of these as a single string, joined with
+//! [`SEPARATOR`], and keeps the single-valued field (e.g. `artist`) that it
+//! was derived from alongside it. Older databases only have the latter, so
+//! the `all_*` methods fall back to splitting it with [`Separators`].
+//!
+//! [`SEPARATOR`]: constant.SEPARATOR.html
+//! [`Separators`]: struct.Separators.html
+
+use std::collections::BTreeSet;
+
+use super::{Album, Item};
+
+/// What beets joins the values of a multi-valued field with.
+pub const SEPARATOR: &str = "\\\u{2400}";
+
+/// Split a multi-valued field as beets stores it.
+#[must_use]
+pub fn split(value: &str) -> Vec<String> {
+    value
+        .split(SEPARATOR)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The separators between artists that are also part of many names, such as
+/// "Simon & Garfunkel" or "Earth, Wind & Fire".
+pub const ARTIST_CONJUNCTIONS: &[&str] = &[" & ", ", "];
+
+/// What to split single-valued fields on, when there is no multi-valued
+/// field to read instead.
+///
+/// By default, artists are only split on markers like `" feat. "`; see
+/// [`with_conjunctions`](#method.with_conjunctions) to split them on `&` and
+/// `,` too.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Separators {
+    /// For `artist` and `albumartist`, e.g. `" feat. "`.
+    pub artist: Vec<String>,
+    /// For `genre`, e.g. `", "`.
+    pub genre: Vec<String>,
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        let strings = |separators: &[&str]| separators.iter().map(|s| (*s).to_string()).collect();
+        Self {
+            artist: strings(&[" feat. ", " ft. ", " featuring "]),
+            genre: strings(&[", ", "; "]),
+        }
+    }
+}
+
+impl Separators {
+    /// Never split single-valued fields.
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            artist: Vec::new(),
+            genre: Vec::new(),
+        }
+    }
+
+    /// Also split artists on [`ARTIST_CONJUNCTIONS`], at the cost of
+    /// splitting the names that have them.
+    ///
+    /// [`ARTIST_CONJUNCTIONS`]: constant.ARTIST_CONJUNCTIONS.html
+    #[must_use]
+    pub fn with_conjunctions(mut self) -> Self {
+        for conjunction in ARTIST_CONJUNCTIONS {
+            if !self.artist.iter().any(|s| s == conjunction) {
+                self.artist.push((*conjunction).to_string());
+            }
+        }
+        self
+    }
+}
+
+/// The values of a multi-valued field, or else those of its single-valued
+/// counterpart split on `separators`, without duplicates.
+fn values(multi: &[String], single: &str, separators: &[String]) -> Vec<String> {
+    if !multi.is_empty() {
+        return multi.to_vec();
+    }
+
+    let mut parts = vec![single.to_string()];
+    for separator in separators {
+        parts = parts
+            .iter()
+            .flat_map(|part| part.split(separator.as_str()))
+            .map(String::from)
+            .collect();
+    }
+
+    let mut seen = BTreeSet::new();
+    parts
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty() && seen.insert(part.clone()))
+        .collect()
+}
+
+impl Album {
+    /// Every artist credited for the album.
+    #[must_use]
+    pub fn all_albumartists(&self, separators: &Separators) -> Vec<String> {
+        values(&self.albumartists, &self.albumartist, &separators.artist)
+    }
+
+    /// Every genre of the album.
+    #[must_use]
+    pub fn all_genres(&self, separators: &Separators) -> Vec<String> {
+        values(&self.genres, &self.genre, &separators.genre)
+    }
+}
+
+impl Item {
+    /// Every artist credited for the item.
+    #[must_use]
+    pub fn all_artists(&self, separators: &Separators) -> Vec<String> {
+        values(&self.artists, &self.artist, &separators.artist)
+    }
+
+    /// Every artist credited for the item's album.
+    #[must_use]
+    pub fn all_albumartists(&self, separators: &Separators) -> Vec<String> {
+        values(&self.albumartists, &self.albumartist, &separators.artist)
+    }
+
+    /// Every genre of the item.
+    #[must_use]
+    pub fn all_genres(&self, separators: &Separators) -> Vec<String> {
+        values(&self.genres, &self.genre, &separators.genre)
+    }
+}
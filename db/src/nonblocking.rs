@@ -0,0 +1,130 @@
+//! Read a beets database without blocking an executor, e.g. inside a tokio
+//! server.
+//!
+//! Every query runs on a dedicated thread pool, with its own connection to
+//! the database, and its result is delivered as a future or a stream.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::sync::mpsc::{self, Receiver};
+use futures::{Async, Future, Poll, Sink, Stream};
+use futures_cpupool::{CpuFuture, CpuPool};
+use rusqlite::Connection;
+
+use super::{open, Album, Error, Item, Iter, Library, Paged, Pages, DEFAULT_PAGE_SIZE};
+
+/// How many entries a stream reads ahead of its consumer.
+const STREAM_BUFFER: usize = 64;
+
+/// A beets database whose queries run on a thread pool.
+#[derive(Clone)]
+pub struct Database {
+    path: Arc<PathBuf>,
+    pool: CpuPool,
+}
+
+impl Database {
+    /// Query the database at `db_path` on a pool with a thread for each CPU.
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(db_path: P) -> Self {
+        Self::with_pool(db_path, CpuPool::new_num_cpus())
+    }
+
+    /// Query the database at `db_path` on the given pool.
+    #[must_use]
+    pub fn with_pool<P: Into<PathBuf>>(db_path: P, pool: CpuPool) -> Self {
+        Self {
+            path: Arc::new(db_path.into()),
+            pool,
+        }
+    }
+
+    /// Run `query` on the pool with a read-only connection to the database.
+    ///
+    /// The database is opened afresh for each query, so the result reflects
+    /// any changes made since the last one.
+    pub fn run<T, F>(&self, query: F) -> CpuFuture<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, Error> + Send + 'static,
+    {
+        let path = Arc::clone(&self.path);
+        self.pool.spawn_fn(move || query(&open(path.as_ref())?))
+    }
+
+    /// Read all of the albums and items, as for
+    /// [`read_all`](../fn.read_all.html).
+    pub fn read_all(&self) -> CpuFuture<(Vec<Album>, Vec<Item>), Error> {
+        self.run(|c| Ok((Album::read_all(c)?, Item::read_all(c)?)))
+    }
+
+    /// Read all of the albums and items into a [`Library`](../struct.Library.html).
+    pub fn library(&self) -> CpuFuture<Library, Error> {
+        self.run(Library::read)
+    }
+
+    /// Stream every album, in order of id.
+    #[must_use]
+    pub fn albums(&self) -> Entries<Album> {
+        self.stream()
+    }
+
+    /// Stream every item, in order of id.
+    #[must_use]
+    pub fn items(&self) -> Entries<Item> {
+        self.stream()
+    }
+
+    /// Read the entries of a table a page at a time on the pool, sending
+    /// each one as soon as there is room for it in the stream.
+    fn stream<T: Paged + Send + 'static>(&self) -> Entries<T> {
+        let (mut tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let path = Arc::clone(&self.path);
+
+        self.pool
+            .spawn_fn(move || {
+                let conn = match open(path.as_ref()) {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        let _ = tx.send(Err(err)).wait();
+                        return Ok(());
+                    }
+                };
+
+                for entry in Iter::new(Pages::new(&conn, DEFAULT_PAGE_SIZE)) {
+                    match tx.send(entry).wait() {
+                        Ok(sender) => tx = sender,
+                        // The stream was dropped, so nobody is listening.
+                        Err(_) => break,
+                    }
+                }
+
+                Ok::<_, ()>(())
+            })
+            .forget();
+
+        Entries { rx }
+    }
+}
+
+/// A stream of the entries of a table, read on a thread pool.
+///
+/// The stream ends after the first error.
+pub struct Entries<T> {
+    rx: Receiver<Result<T, Error>>,
+}
+
+impl<T> Stream for Entries<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<T>, Error> {
+        match self.rx.poll() {
+            Ok(Async::Ready(Some(Ok(entry)))) => Ok(Async::Ready(Some(entry))),
+            Ok(Async::Ready(Some(Err(err)))) => Err(err),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(None)) | Err(()) => Ok(Async::Ready(None)),
+        }
+    }
+}
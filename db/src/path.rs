@@ -0,0 +1,115 @@
+//! Conversions between the raw bytes that beets stores for paths and
+//! [`PathBuf`](https://doc.rust-lang.org/std/path/struct.PathBuf.html).
+//!
+//! On Unix these are lossless, so a path with a name that is not valid UTF-8
+//! still points at its file. Elsewhere, invalid UTF-8 is
+//! [transcribed as the replacement character.](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy)
+
+use std::path::{Path, PathBuf};
+
+/// Convert the bytes of a path as stored by beets.
+#[cfg(unix)]
+#[must_use]
+pub fn from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    OsString::from_vec(bytes).into()
+}
+
+/// Convert the bytes of a path as stored by beets.
+#[cfg(not(unix))]
+#[must_use]
+pub fn from_bytes(bytes: Vec<u8>) -> PathBuf {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
+
+/// Get the bytes of a path as beets would store them.
+#[cfg(unix)]
+#[must_use]
+pub fn to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str().as_bytes().to_vec()
+}
+
+/// Get the bytes of a path as beets would store them.
+#[cfg(not(unix))]
+#[must_use]
+pub fn to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+/// How a path is serialized: as a string if it is valid UTF-8, or
+/// otherwise as `{"bytes": [...]}` so that it can be read back exactly.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum Repr {
+    Str(String),
+    Bytes { bytes: Vec<u8> },
+}
+
+#[cfg(feature = "serde")]
+impl Repr {
+    fn new(path: &Path) -> Self {
+        match path.to_str() {
+            Some(s) => Repr::Str(s.to_string()),
+            None => Repr::Bytes {
+                bytes: to_bytes(path),
+            },
+        }
+    }
+
+    fn into_path(self) -> PathBuf {
+        match self {
+            Repr::Str(s) => s.into(),
+            Repr::Bytes { bytes } => from_bytes(bytes),
+        }
+    }
+}
+
+/// Serialize a path reversibly, for use with `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Repr;
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr::new(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        Repr::deserialize(deserializer).map(Repr::into_path)
+    }
+}
+
+/// Serialize an optional path reversibly, for use with
+/// `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_optional_path {
+    use std::path::PathBuf;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Repr;
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S: Serializer>(
+        path: &Option<PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_ref()
+            .map(|path| Repr::new(path))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PathBuf>, D::Error> {
+        Option::<Repr>::deserialize(deserializer).map(|repr| repr.map(Repr::into_path))
+    }
+}
@@ -0,0 +1,146 @@
+//! Aggregate statistics about a library, like `beet stats -e` gives.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use super::multi::Separators;
+use super::{Item, Library};
+
+/// The width of the buckets of [`Stats::bitrates`](struct.Stats.html#structfield.bitrates),
+/// in bits per second.
+pub const BITRATE_BUCKET: u32 = 64_000;
+
+/// How many artists [`Stats::top_artists`](struct.Stats.html#structfield.top_artists)
+/// has at most.
+pub const TOP_ARTISTS: usize = 10;
+
+/// How many items share a value, and how long they take to play.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Tally {
+    pub items: usize,
+    /// In seconds.
+    pub duration: f64,
+}
+
+impl Tally {
+    fn add(&mut self, item: &Item) {
+        self.items += 1;
+        self.duration += item.length;
+    }
+}
+
+/// Statistics about a library.
+///
+/// Items with an empty value, or a year, bitrate or sample rate of 0, are left
+/// out of the tallies for that field.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Stats {
+    pub albums: usize,
+    pub items: usize,
+    /// The number of distinct artists of items.
+    pub artists: usize,
+    /// The number of distinct artists of albums.
+    pub albumartists: usize,
+    /// The total length of the items, in seconds.
+    pub duration: f64,
+    /// The total size of the items' files, in bytes.
+    pub size: u64,
+    pub formats: BTreeMap<String, Tally>,
+    pub genres: BTreeMap<String, Tally>,
+    /// By the first year of each decade, e.g. 1990.
+    pub decades: BTreeMap<u16, Tally>,
+    pub labels: BTreeMap<String, Tally>,
+    pub countries: BTreeMap<String, Tally>,
+    /// The number of items by bitrate, rounded down to a multiple of
+    /// [`BITRATE_BUCKET`](constant.BITRATE_BUCKET.html).
+    pub bitrates: BTreeMap<u32, usize>,
+    /// The number of items by sample rate.
+    pub samplerates: BTreeMap<u32, usize>,
+    /// The artists with the most items, and how many they have, most first.
+    pub top_artists: Vec<(String, usize)>,
+}
+
+impl Stats {
+    /// Compute the statistics of `library`, estimating the size of each
+    /// file from its bitrate and length.
+    #[must_use]
+    pub fn new(library: &Library, separators: &Separators) -> Self {
+        Self::collect(library, separators, estimated_size)
+    }
+
+    /// Compute the statistics of `library`, reading the size of each file.
+    /// Files that cannot be read are estimated instead.
+    #[must_use]
+    pub fn exact(library: &Library, separators: &Separators) -> Self {
+        Self::collect(library, separators, |item| {
+            fs::metadata(&item.path).map_or_else(|_| estimated_size(item), |m| m.len())
+        })
+    }
+
+    fn collect(library: &Library, separators: &Separators, size: impl Fn(&Item) -> u64) -> Self {
+        let mut stats = Self {
+            albums: library.album_count(),
+            items: library.item_count(),
+            albumartists: library
+                .albums()
+                .flat_map(|album| album.all_albumartists(separators))
+                .collect::<BTreeSet<_>>()
+                .len(),
+            ..Self::default()
+        };
+
+        let mut artists = BTreeMap::<_, usize>::new();
+        for item in library.items() {
+            let item = item.as_item();
+            stats.duration += item.length;
+            stats.size += size(item);
+
+            let tally = |tallies: &mut BTreeMap<String, Tally>, value: &str| {
+                if !value.is_empty() {
+                    tallies.entry(value.to_string()).or_default().add(item);
+                }
+            };
+            tally(&mut stats.formats, &item.format);
+            for genre in item.all_genres(separators) {
+                tally(&mut stats.genres, &genre);
+            }
+            tally(&mut stats.labels, &item.label);
+            tally(&mut stats.countries, &item.country);
+            if item.year != 0 {
+                stats
+                    .decades
+                    .entry(item.year / 10 * 10)
+                    .or_default()
+                    .add(item);
+            }
+
+            if item.bitrate != 0 {
+                *stats
+                    .bitrates
+                    .entry(item.bitrate / BITRATE_BUCKET * BITRATE_BUCKET)
+                    .or_default() += 1;
+            }
+            if item.samplerate != 0 {
+                *stats.samplerates.entry(item.samplerate).or_default() += 1;
+            }
+            for artist in item.all_artists(separators) {
+                *artists.entry(artist).or_default() += 1;
+            }
+        }
+
+        stats.artists = artists.len();
+        let mut top_artists = artists.into_iter().collect::<Vec<_>>();
+        top_artists.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_artists.truncate(TOP_ARTISTS);
+        stats.top_artists = top_artists;
+        stats
+    }
+}
+
+/// The size of an item's file from its bitrate and length, in bytes.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn estimated_size(item: &Item) -> u64 {
+    (f64::from(item.bitrate) * item.length / 8.0) as u64
+}
@@ -0,0 +1,496 @@
+//! Evaluate the templates of beets' `paths:` option, such as
+//! `$albumartist/$album%aunique{}/$track $title`, to predict where beets
+//! would put a file.
+//!
+//! As in beets, parsing never fails: anything that is not a well-formed field
+//! or function call is kept as text, and so are fields and functions that do
+//! not exist. The functions are `%lower`, `%upper`, `%capitalize`, `%title`,
+//! `%left`, `%right`, `%if`, `%ifdef`, `%first` and `%aunique`.
+//!
+//! Fields are formatted as beets formats them for paths: e.g. `$track` is
+//! padded to two digits, `$length` is `m:ss`, `/` becomes `_`, and an item's
+//! album overrides the fields that they share. Dates are in the system's time
+//! zone, as beets shows them, unless the template is given another.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+use super::{local_time, AlbumRef, ItemRef, Value};
+
+/// The functions, with the least and most arguments that they take.
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("capitalize", 1, 1),
+    ("title", 1, 1),
+    ("left", 2, 2),
+    ("right", 2, 2),
+    ("if", 2, 3),
+    ("ifdef", 1, 3),
+    ("first", 1, 5),
+    ("aunique", 0, 3),
+];
+
+/// What `%aunique` compares and tells albums apart by, unless it is told.
+const AUNIQUE_KEYS: &str = "albumartist album";
+const AUNIQUE_DISAMBIGUATORS: &str =
+    "albumtype year label catalognum albumdisambig releasegroupdisambig";
+const AUNIQUE_BRACKET: &str = "[]";
+
+/// A parsed template.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+    /// Seconds ahead of UTC to show dates in, rather than the system's.
+    utc_offset: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    /// A field, and the text it was parsed from.
+    Field(String, String),
+    /// A function, its arguments, and the text it was parsed from.
+    Call(String, Vec<Template>, String),
+}
+
+/// The item or album that a template is evaluated for.
+#[derive(Clone, Copy)]
+struct Context<'l> {
+    item: Option<ItemRef<'l>>,
+    album: Option<AlbumRef<'l>>,
+    utc_offset: Option<i64>,
+}
+
+impl Template {
+    /// Parse `source`.
+    #[must_use]
+    pub fn parse(source: &str) -> Self {
+        let (mut template, end) = Self::parse_expression(source, false);
+        // A stray `}` ends the template early; beets keeps the rest as text.
+        template.push_text(&source[end..]);
+        template
+    }
+
+    /// Show dates such as `$added` in a time zone that is always `offset`
+    /// seconds ahead of UTC, rather than in the system's.
+    #[must_use]
+    pub fn with_utc_offset(mut self, offset: i64) -> Self {
+        self.utc_offset = Some(offset);
+        self
+    }
+
+    /// Evaluate the template for `item`, with the fields of its album.
+    #[must_use]
+    pub fn evaluate_item(&self, item: ItemRef<'_>) -> String {
+        self.evaluate(Context {
+            item: Some(item),
+            album: item.album(),
+            utc_offset: self.utc_offset,
+        })
+    }
+
+    /// Evaluate the template for `album`.
+    #[must_use]
+    pub fn evaluate_album(&self, album: AlbumRef<'_>) -> String {
+        self.evaluate(Context {
+            item: None,
+            album: Some(album),
+            utc_offset: self.utc_offset,
+        })
+    }
+
+    /// Parse up to the end of `source`, or to a `}` (or `,` in an argument),
+    /// returning the template and the position it stopped at.
+    fn parse_expression(source: &str, in_argument: bool) -> (Self, usize) {
+        let is_special = |c| matches!(c, '$' | '%' | '{' | '}') || (in_argument && c == ',');
+
+        let mut template = Self::default();
+        let mut pos = 0;
+        while let Some(c) = source[pos..].chars().next() {
+            if !is_special(c) {
+                let end = source[pos..]
+                    .find(is_special)
+                    .map_or(source.len(), |i| pos + i);
+                template.push_text(&source[pos..end]);
+                pos = end;
+                continue;
+            }
+
+            pos = match (c, source[pos + 1..].chars().next()) {
+                ('}' | ',', _) => break,
+                // Nothing can start at the last character.
+                (_, None) => {
+                    template.push_text(&source[pos..]);
+                    source.len()
+                }
+                ('$', Some(escaped @ ('$' | '%' | '}' | ','))) => {
+                    template.push_text(escaped.encode_utf8(&mut [0; 4]));
+                    pos + 2
+                }
+                ('$', _) => template.parse_field(source, pos),
+                ('%', _) => template.parse_call(source, pos),
+                _ => {
+                    template.push_text("{");
+                    pos + 1
+                }
+            };
+        }
+        (template, pos)
+    }
+
+    /// Parse the field that starts with the `$` at `start`, and return where
+    /// it ends.
+    fn parse_field(&mut self, source: &str, start: usize) -> usize {
+        let rest = &source[start + 1..];
+        let (name, end) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(len) if len > 0 => (&braced[..len], start + len + 3),
+                _ => {
+                    self.push_text("${");
+                    return start + 2;
+                }
+            }
+        } else {
+            let len = ident_len(rest);
+            if len == 0 {
+                self.push_text("$");
+                return start + 1;
+            }
+            (&rest[..len], start + len + 1)
+        };
+        self.parts.push(Part::Field(
+            name.to_string(),
+            source[start..end].to_string(),
+        ));
+        end
+    }
+
+    /// Parse the function call that starts with the `%` at `start`, and
+    /// return where it ends.
+    fn parse_call(&mut self, source: &str, start: usize) -> usize {
+        let name = &source[start + 1..][..ident_len(&source[start + 1..])];
+        if name.is_empty() {
+            self.push_text("%");
+            return start + 1;
+        }
+
+        let mut pos = start + 1 + name.len();
+        if !source[pos..].starts_with('{') {
+            self.push_text(&source[start..pos]);
+            return pos;
+        }
+
+        pos += 1;
+        let mut args = Vec::new();
+        while pos < source.len() {
+            let (arg, len) = Self::parse_expression(&source[pos..], true);
+            args.push(arg);
+            pos += len;
+            if !source[pos..].starts_with(',') {
+                break;
+            }
+            pos += 1;
+        }
+
+        if !source[pos..].starts_with('}') {
+            self.push_text(&source[start..pos]);
+            return pos;
+        }
+        pos += 1;
+        self.parts.push(Part::Call(
+            name.to_string(),
+            args,
+            source[start..pos].to_string(),
+        ));
+        pos
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.parts.last_mut() {
+            Some(Part::Text(last)) => last.push_str(text),
+            _ => self.parts.push(Part::Text(text.to_string())),
+        }
+    }
+
+    fn evaluate(&self, context: Context<'_>) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field(name, source) => {
+                    out.push_str(&context.field(name).unwrap_or_else(|| source.clone()));
+                }
+                Part::Call(name, args, source) => {
+                    let args = args
+                        .iter()
+                        .map(|arg| arg.evaluate(context))
+                        .collect::<Vec<_>>();
+                    match context.call(name, &args) {
+                        Some(Ok(value)) => out.push_str(&value),
+                        // beets shows what went wrong in place of the call.
+                        Some(Err(err)) => {
+                            out.push('<');
+                            out.push_str(&err);
+                            out.push('>');
+                        }
+                        None => out.push_str(source),
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Context<'_> {
+    /// The value of a field, with the album's taking precedence.
+    fn get(&self, name: &str) -> Option<Value> {
+        let album = self.album.filter(|_| name != "id" || self.item.is_none());
+        album
+            .and_then(|album| album.get(name))
+            .or_else(|| self.item.and_then(|item| item.get(name)))
+    }
+
+    /// The value of a field, formatted for a path.
+    fn field(&self, name: &str) -> Option<String> {
+        let formatted = |name| {
+            self.get(name)
+                .map(|value| format(name, &value, self.utc_offset))
+        };
+        let mut value = formatted(name)?;
+        // An empty artist or album artist falls back to the other.
+        let fallback = match name {
+            "artist" => "albumartist",
+            "albumartist" => "artist",
+            _ => "",
+        };
+        if value.is_empty() && !fallback.is_empty() {
+            value = formatted(fallback).unwrap_or_default();
+        }
+        Some(value.replace('/', "_"))
+    }
+
+    /// Call a function, or return `None` if there is no such function.
+    fn call(&self, name: &str, args: &[String]) -> Option<Result<String, String>> {
+        let &(_, min, max) = FUNCTIONS.iter().find(|(function, ..)| *function == name)?;
+        if args.len() < min || args.len() > max {
+            return Some(Err(format!(
+                "%{name} takes {min} to {max} arguments, not {}",
+                args.len()
+            )));
+        }
+        Some(self.apply(name, args))
+    }
+
+    fn apply(&self, name: &str, args: &[String]) -> Result<String, String> {
+        let arg = |i: usize| args.get(i).map_or("", String::as_str);
+        Ok(match name {
+            "lower" => arg(0).to_lowercase(),
+            "upper" => arg(0).to_uppercase(),
+            "capitalize" => capitalize(arg(0)),
+            "title" => arg(0)
+                .split_whitespace()
+                .map(capitalize)
+                .collect::<Vec<_>>()
+                .join(" "),
+            "left" => slice(arg(0), 0, Some(int(arg(1))?)),
+            "right" => {
+                let count = int(arg(1))?;
+                let start = count
+                    .checked_neg()
+                    .ok_or_else(|| format!("out of range: {count}"))?;
+                slice(arg(0), start, None)
+            }
+            "if" => {
+                let condition = arg(0);
+                let holds = match condition.trim().parse::<i64>() {
+                    Ok(n) => n != 0,
+                    Err(_) => !condition.is_empty() && !condition.eq_ignore_ascii_case("false"),
+                };
+                if holds { arg(1) } else { arg(2) }.to_string()
+            }
+            "ifdef" => match self.get(arg(0)) {
+                Some(_) if arg(1).is_empty() => self.field(arg(0)).unwrap_or_default(),
+                Some(_) => arg(1).to_string(),
+                None => arg(2).to_string(),
+            },
+            "first" => {
+                let or = |i, default| args.get(i).map_or(default, String::as_str);
+                let skip = usize::try_from(int(or(2, "0"))?).map_err(|err| err.to_string())?;
+                let count = usize::try_from(int(or(1, "1"))?).map_err(|err| err.to_string())?;
+                let separator = or(3, "; ");
+                if separator.is_empty() {
+                    return Err("empty separator".to_string());
+                }
+                arg(0)
+                    .split(separator)
+                    .skip(skip)
+                    .take(count)
+                    .collect::<Vec<_>>()
+                    .join(or(4, "; "))
+            }
+            "aunique" => self.aunique(
+                arg(0),
+                arg(1),
+                args.get(2).map_or(AUNIQUE_BRACKET, String::as_str),
+            ),
+            _ => unreachable!("no function `{}`", name),
+        })
+    }
+
+    /// Tell the album apart from others with the same `keys`, by the first
+    /// of `disambiguators` that differs between all of them, or else by id.
+    fn aunique(&self, keys: &str, disambiguators: &str, bracket: &str) -> String {
+        let Some(album) = self.album else {
+            return String::new();
+        };
+        let keys = if keys.is_empty() { AUNIQUE_KEYS } else { keys };
+        let disambiguators = if disambiguators.is_empty() {
+            AUNIQUE_DISAMBIGUATORS
+        } else {
+            disambiguators
+        };
+        let (open, close) = match bracket.chars().collect::<Vec<_>>()[..] {
+            [open, close] => (open.to_string(), close.to_string()),
+            _ => (String::new(), String::new()),
+        };
+
+        let keys = keys.split_whitespace().collect::<Vec<_>>();
+        let albums = album
+            .library()
+            .albums()
+            .filter(|other| keys.iter().all(|key| other.get(key) == album.get(key)))
+            .collect::<Vec<_>>();
+        if albums.len() < 2 {
+            return String::new();
+        }
+
+        let text = |album: &AlbumRef<'_>, name| {
+            album
+                .get(name)
+                .and_then(|value| value.as_text().map(Cow::into_owned))
+                .unwrap_or_default()
+        };
+        let disambiguator = disambiguators.split_whitespace().find(|name| {
+            let mut values = albums
+                .iter()
+                .map(|album| text(album, name))
+                .collect::<Vec<_>>();
+            values.sort();
+            values.dedup();
+            values.len() == albums.len()
+        });
+        let value = match disambiguator {
+            Some(name) => album
+                .get(name)
+                .map(|value| format(name, &value, self.utc_offset).replace('/', "_"))
+                .unwrap_or_default(),
+            None => album.id.to_string(),
+        };
+        if value.is_empty() {
+            String::new()
+        } else {
+            format!(" {open}{value}{close}")
+        }
+    }
+}
+
+/// The length of the identifier at the start of `text`.
+fn ident_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len())
+}
+
+/// Format a value as beets does for the field `name`, with dates
+/// `utc_offset` seconds ahead of UTC, or in the system's time zone.
+fn format(name: &str, value: &Value, utc_offset: Option<i64>) -> String {
+    match (name, value) {
+        ("year" | "original_year", Value::Integer(n)) => format!("{n:04}"),
+        (
+            "track" | "tracktotal" | "disc" | "disctotal" | "month" | "day" | "original_month"
+            | "original_day",
+            Value::Integer(n),
+        ) => format!("{n:02}"),
+        ("comp", Value::Integer(n)) => if *n == 0 { "False" } else { "True" }.to_string(),
+        ("bitrate", Value::Integer(n)) => format!("{}kbps", n / 1000),
+        ("samplerate", Value::Integer(n)) => format!("{}kHz", n / 1000),
+        ("length", Value::Real(seconds)) => {
+            let seconds = whole(*seconds);
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+        ("added" | "mtime", Value::Real(seconds)) => {
+            let seconds = whole(*seconds);
+            date(seconds + utc_offset.unwrap_or_else(|| local_time::utc_offset(seconds)))
+        }
+        (_, Value::Real(n)) => format!("{n:.1}"),
+        (_, value) => value.as_text().unwrap_or_default().into_owned(),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn whole(seconds: f64) -> i64 {
+    seconds.floor() as i64
+}
+
+/// `seconds` since the Unix epoch, as if local time were UTC, as
+/// `YYYY-MM-DD HH:MM:SS`.
+fn date(seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // From Howard Hinnant's `civil_from_days`.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// The first character in upper case and the rest in lower case.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    })
+}
+
+/// The characters from `start` to `end`, counting from the end if they are
+/// negative, as Python slices strings.
+fn slice(text: &str, start: i64, end: Option<i64>) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let len = i64::try_from(chars.len()).unwrap_or(i64::MAX);
+    let index = |i: i64| {
+        let i = if i < 0 { (len + i).max(0) } else { i.min(len) };
+        usize::try_from(i).unwrap_or_default()
+    };
+    let (start, end) = (index(start), end.map_or(chars.len(), index));
+    chars
+        .get(start..end)
+        .map_or_else(String::new, |chars| chars.iter().collect())
+}
+
+fn int(text: &str) -> Result<i64, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("not a number: {text:?}"))
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::filter::{Condition, Filter};
+
+#[cfg(feature = "sqlite")]
+fn memory(conn: &Connection) -> Result<Memory> {
+    Ok(Memory::new(Album::read_all(conn)?, Item::read_all(conn)?))
+}
+
+/// Part of the 1.4.7 fixture, built without reading it from `SQLite`.
+fn sample() -> Memory {
+    let album = Album {
+        id: 1,
+        album: "Geogaddi".to_string(),
+        albumartist: "Boards of Canada".to_string(),
+        ..Album::default()
+    };
+    let item = |id, album_id, title: &str, track| Item {
+        id,
+        album_id,
+        title: title.to_string(),
+        track,
+        ..Item::default()
+    };
+    let mut items = vec![
+        item(1, Some(1), "Ready Lets Go", 1),
+        item(2, Some(1), "Music Is Math", 2),
+        item(3, Some(2), "Come Together", 1),
+        item(4, None, "Windowlicker", 1),
+    ];
+    items[0].flex.insert("rating".to_string(), "4".to_string());
+    let abbey_road = Album {
+        id: 2,
+        album: "Abbey Road".to_string(),
+        ..Album::default()
+    };
+    Memory::new(vec![album, abbey_road], items)
+}
+
+#[test]
+fn memory_filters() -> Result<()> {
+    let memory = sample();
+    let ids = |filter: &Filter| -> Result<Vec<u32>> {
+        Ok(memory.items(filter)?.iter().map(|i| i.id).collect())
+    };
+
+    assert_eq!(
+        ids(&Filter::new().with(Condition::is("album_id", 1)))?,
+        [1, 2]
+    );
+    assert_eq!(ids(&Filter::new().sort_by("title", true).limit(2))?, [3, 2]);
+    assert_eq!(
+        ids(&Filter::new().with(Condition::contains("rating", "4")))?,
+        [1]
+    );
+    let albums = memory.albums(&Filter::new().with(Condition::contains("album", "abbey")))?;
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0].id, 2);
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn memory_reads_like_sqlite() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let memory = memory(&conn)?;
+
+    for filter in &[
+        Filter::new(),
+        Filter::new().with(Condition::is("album_id", 1)),
+        Filter::new().sort_by("title", false).limit(2),
+        Filter::new().with(Condition::contains("rating", "4")),
+    ] {
+        assert_eq!(memory.items(filter)?, conn.items(filter)?, "{filter:?}");
+        assert_eq!(memory.albums(filter)?, conn.albums(filter)?, "{filter:?}");
+    }
+
+    let library = Library::load(&memory)?;
+    assert_eq!((library.album_count(), library.item_count()), (2, 4));
+    Ok(())
+}
+
+#[test]
+fn memory_insert_and_remove() -> Result<()> {
+    let mut memory = sample();
+
+    let mut item = memory.remove_item(3).unwrap();
+    assert_eq!(memory.remove_item(3), None);
+    item.id = 7;
+    item.title = "Dig It".to_string();
+    assert_eq!(memory.insert_item(item.clone()), None);
+    assert!(memory.remove_album(2).is_some());
+
+    let all = Filter::new();
+    let ids = memory.items(&all)?.iter().map(|i| i.id).collect::<Vec<_>>();
+    assert_eq!(ids, [1, 2, 4, 7]);
+    assert_eq!(memory.albums(&all)?.len(), 1);
+
+    let library = Library::load(&memory)?;
+    assert_eq!(library.item(7).map(|i| i.title.clone()), Some(item.title));
+    assert!(library.item(7).unwrap().album().is_none());
+    Ok(())
+}
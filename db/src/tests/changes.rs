@@ -0,0 +1,57 @@
+use super::*;
+
+fn ids<T: Paged>(entries: &[T]) -> Vec<u32> {
+    entries.iter().map(Paged::id).collect()
+}
+
+#[test]
+fn items_changed_since() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    assert_eq!(
+        ids(&Item::read_changed_since(&conn, 1_548_000_000.0)?),
+        [3, 4]
+    );
+    assert!(Item::read_changed_since(&conn, 1_600_000_000.0)?.is_empty());
+
+    conn.execute_batch("UPDATE items SET mtime = 1600000001 WHERE id = 1;")?;
+    let changed = Item::read_changed_since(&conn, 1_600_000_000.0)?;
+    assert_eq!(ids(&changed), [1]);
+    assert_eq!(changed[0].flex.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn albums_changed_since() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    assert_eq!(
+        ids(&Album::read_changed_since(&conn, 1_548_000_000.0)?),
+        [2]
+    );
+    assert!(Album::read_changed_since(&conn, 1_560_000_000.0)?.is_empty());
+
+    conn.execute_batch("UPDATE items SET mtime = 1600000001 WHERE id = 2;")?;
+    let changed = Album::read_changed_since(&conn, 1_560_000_000.0)?;
+    assert_eq!(ids(&changed), [1]);
+    assert_eq!(
+        changed[0].flex.get("mood").map(String::as_str),
+        Some("hazy")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn removed_ids() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let known = Item::read_ids(&conn)?;
+    assert_eq!(known.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+    conn.execute_batch("DELETE FROM items WHERE id IN (2, 4);")?;
+    assert_eq!(Item::read_removed(&conn, known)?, [2, 4]);
+    assert_eq!(Album::read_removed(&conn, vec![1, 2, 7])?, [7]);
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use super::*;
+use crate::check::{check, Problem};
+
+#[test]
+fn problems_are_reported() -> Result<()> {
+    let dir = env::temp_dir().join("beet_db-check");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut albums = Album::read_all(&conn)?;
+    let mut items = Item::read_all(&conn)?;
+
+    // Item 1 is intact, item 2 was edited after import, item 3 was moved,
+    // and item 4 claims item 1's file and an album that was removed.
+    for (item, name) in items.iter_mut().zip(&["1.flac", "2.flac", "3.mp3"]) {
+        item.path = dir.join(name);
+        if item.id != 3 {
+            fs::write(&item.path, b"").unwrap();
+        }
+        item.mtime = fs::metadata(&item.path).map_or(0.0, |m| {
+            let modified = m.modified().unwrap();
+            modified.duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+        });
+    }
+    items[1].mtime -= 60.0;
+    items[3].path = items[0].path.clone();
+    items[3].mtime = items[0].mtime;
+    items[3].album_id = Some(9);
+    albums[0].artpath = Some(dir.join("cover.jpg"));
+    let stale = items[1].mtime;
+
+    let report = check(&Library::new(albums, items));
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.problems,
+        [
+            Problem::Modified {
+                item: 2,
+                path: dir.join("2.flac"),
+                mtime: stale,
+                modified: stale + 60.0,
+            },
+            Problem::MissingFile {
+                item: 3,
+                path: dir.join("3.mp3"),
+            },
+            Problem::MissingAlbum {
+                item: 4,
+                album_id: 9,
+            },
+            Problem::MissingArt {
+                album: 1,
+                path: dir.join("cover.jpg"),
+            },
+            Problem::DuplicatePath {
+                path: dir.join("1.flac"),
+                items: vec![1, 4],
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn empty_albums_are_reported() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut albums = Album::read_all(&conn)?;
+    let mut items = Item::read_all(&conn)?;
+    items.retain(|item| item.id == 1);
+    for item in &mut items {
+        item.path = env::current_exe().unwrap();
+        item.mtime = 0.0;
+    }
+    for album in &mut albums {
+        album.artpath = None;
+    }
+
+    let report = check(&Library::new(albums, items));
+    assert_eq!(report.problems, [Problem::EmptyAlbum { album: 2 }]);
+    Ok(())
+}
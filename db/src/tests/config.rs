@@ -0,0 +1,92 @@
+use std::env;
+use std::fs;
+
+use super::*;
+use crate::config::Config;
+
+/// Make an empty configuration directory named after the test.
+fn config_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("beet_db-config-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn defaults_without_config_file() -> Result<()> {
+    let dir = config_dir("defaults");
+    let config = Config::from_dir(&dir)?;
+
+    assert_eq!(config.library, dir.join("library.db"));
+    assert!(config.directory.ends_with("Music"));
+    assert_eq!(config.paths[0].0, "default");
+    assert_eq!(config.paths.len(), 3);
+    assert!(config.plugins.is_empty());
+    Ok(())
+}
+
+#[test]
+fn read_config_with_includes() -> Result<()> {
+    let dir = config_dir("includes");
+    fs::write(
+        dir.join("config.yaml"),
+        "library: /data/beets/library.db
+directory: music
+plugins: fetchart lastgenre
+include:
+  - paths.yaml
+  - plugins.yaml
+paths:
+  default: $albumartist/$album/$track $title
+",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("paths.yaml"),
+        "paths:
+  genre:Classical: Classical/$composer/$album/$track $title
+  default: $albumartist/$year - $album/$track $title
+",
+    )
+    .unwrap();
+    fs::write(dir.join("plugins.yaml"), "plugins: [fetchart, web]\n").unwrap();
+
+    let config = Config::from_dir(&dir)?;
+    assert_eq!(config.library, Path::new("/data/beets/library.db"));
+    assert_eq!(config.directory, dir.join("music"));
+    assert_eq!(config.plugins, ["fetchart", "web"]);
+
+    let queries = config
+        .paths
+        .iter()
+        .map(|(q, _)| q.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(queries, ["genre:Classical", "default", "singleton", "comp"]);
+    assert_eq!(
+        config.paths[1].1,
+        "$albumartist/$year - $album/$track $title"
+    );
+    Ok(())
+}
+
+#[test]
+fn bad_config() {
+    let dir = config_dir("bad");
+    fs::write(dir.join("config.yaml"), "library: [1, 2]\n").unwrap();
+    match Config::from_dir(&dir) {
+        Err(Error::InvalidConfig { key, .. }) => assert_eq!(key, "library"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    fs::write(dir.join("config.yaml"), "library: \"unterminated\n").unwrap();
+    match Config::from_dir(&dir) {
+        Err(Error::ConfigSyntax { path, .. }) => assert_eq!(path, dir.join("config.yaml")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    fs::write(dir.join("config.yaml"), "include: missing.yaml\n").unwrap();
+    match Config::from_dir(&dir) {
+        Err(Error::Io { path, .. }) => assert_eq!(path, dir.join("missing.yaml")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,78 @@
+use super::*;
+use crate::duplicates::{find, Cluster, Match, DEFAULT_LENGTH_TOLERANCE};
+
+fn cluster(by: Match, ids: &[u32], best: u32) -> Cluster {
+    Cluster {
+        by,
+        ids: ids.to_vec(),
+        best,
+    }
+}
+
+#[test]
+fn clusters_and_best_members() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut albums = Album::read_all(&conn)?;
+    let mut items = Item::read_all(&conn)?;
+
+    // A lossy copy of Geogaddi, matching by fingerprint.
+    let mut album = albums[0].clone();
+    album.id = 3;
+    albums.push(album);
+    for album in &mut albums {
+        if album.id != 2 {
+            album.mb_albumid = "c1d1a8c5".to_string();
+        }
+    }
+    let mut lossy = items[0].clone();
+    lossy.id = 5;
+    lossy.album_id = Some(3);
+    lossy.format = "MP3".to_string();
+    lossy.bitdepth = 0;
+    lossy.length += 1.0;
+    items[0].acoustid_fingerprint = "AQADtE".to_string();
+    lossy.acoustid_fingerprint = "AQADtE".to_string();
+
+    // A better copy of Come Together, matching by AcoustID despite its length.
+    let mut better = items[2].clone();
+    better.id = 6;
+    better.format = "flac".to_string();
+    better.bitdepth = 24;
+    better.length += 30.0;
+    items[2].acoustid_id = "0b6fd3c4".to_string();
+    better.acoustid_id = "0b6fd3c4".to_string();
+
+    // The same recording, and a copy with different case and punctuation.
+    items[1].mb_trackid = "6f3c2b0e".to_string();
+    items[3].mb_trackid = "6f3c2b0e".to_string();
+    let mut copy = items[3].clone();
+    copy.id = 7;
+    copy.mb_trackid = String::new();
+    copy.title = "Windowlicker!".to_string();
+    copy.artist = "APHEX TWIN".to_string();
+    copy.length -= 1.5;
+
+    items.extend(vec![lossy, better, copy]);
+    let library = Library::new(albums, items);
+    let duplicates = find(&library, DEFAULT_LENGTH_TOLERANCE);
+    assert_eq!(
+        duplicates.items,
+        [
+            cluster(Match::MbTrackId, &[2, 4], 2),
+            cluster(Match::AcoustId, &[3, 6], 6),
+            cluster(Match::Fingerprint, &[1, 5], 1),
+            cluster(Match::Metadata, &[4, 7], 4),
+        ]
+    );
+    assert_eq!(duplicates.albums, [cluster(Match::MbAlbumId, &[1, 3], 1)]);
+
+    assert!(find(&library, 1.0).items.iter().all(|c| c.ids != [4, 7]));
+    Ok(())
+}
+
+#[test]
+fn no_duplicates() -> Result<()> {
+    let library = Library::read(&fixture(BEETS_1_4_7)?)?;
+    assert!(find(&library, DEFAULT_LENGTH_TOLERANCE).is_empty());
+    Ok(())
+}
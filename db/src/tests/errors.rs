@@ -0,0 +1,74 @@
+use std::env;
+
+use super::*;
+
+#[test]
+fn missing_file() {
+    match open("tests/no-such.db") {
+        Err(Error::NotFound(path)) => assert_eq!(path, Path::new("tests/no-such.db")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn not_a_database() {
+    match open("Cargo.toml") {
+        Err(Error::NotBeets(path)) => assert_eq!(path, Path::new("Cargo.toml")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn database_without_beets_tables() -> Result<()> {
+    let path = env::temp_dir().join("beet_db-not-beets.db");
+    Connection::open(&path)?.execute_batch("CREATE TABLE IF NOT EXISTS songs (id INTEGER);")?;
+
+    match open(&path) {
+        Err(Error::NotBeets(p)) => assert_eq!(p, path),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn missing_id_column() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch(
+        "ALTER TABLE items RENAME TO old_items;
+         CREATE TABLE items AS SELECT path, title FROM old_items;",
+    )?;
+
+    match Item::read_all(&conn) {
+        Err(Error::UnsupportedSchema { table, column }) => {
+            assert_eq!((table, column), ("items", "id"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bad_value() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch("UPDATE items SET year = 'nineteen sixty-nine' WHERE id = 3;")?;
+
+    match Item::read_all(&conn) {
+        Err(err @ Error::BadValue { .. }) => {
+            assert!(err
+                .to_string()
+                .starts_with("Row 3 of table `items` has a bad value in column `year`: "));
+            match err {
+                Error::BadValue {
+                    source: rusqlite::Error::InvalidColumnType(..),
+                    ..
+                } => (),
+                other => panic!("unexpected source: {:?}", other),
+            }
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
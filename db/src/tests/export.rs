@@ -0,0 +1,104 @@
+use super::*;
+use crate::export::{ColumnarReader, Export, Format};
+
+fn export<T: crate::filter::Entity + serde::Serialize + Paged>(
+    conn: &Connection,
+    export: &Export,
+) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    export.write(Iter::new(Pages::<T>::new(conn, 3)), &mut out)?;
+    Ok(out)
+}
+
+#[test]
+fn json_lines_round_trip() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let out = export::<Item>(&conn, &Export::new(Format::JsonLines))?;
+
+    let items = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            // `Item` does not deserialize the timestamps, so take them out.
+            let mut json = serde_json::from_str::<serde_json::Value>(line).unwrap();
+            let object = json.as_object_mut().unwrap();
+            let mtime = object.remove("mtime").unwrap().as_f64().unwrap();
+            let added = object.remove("added").unwrap().as_f64().unwrap();
+            let item = serde_json::from_value::<Item>(json).unwrap();
+            Item {
+                mtime,
+                added,
+                ..item
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(items, Item::read_all(&conn)?);
+    assert_eq!(items[0].flex["rating"], "4");
+    Ok(())
+}
+
+#[test]
+fn csv_with_chosen_columns() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let columns = ["id", "title", "rating"].iter().map(ToString::to_string);
+    let out = export::<Item>(&conn, &Export::new(Format::Csv).columns(columns.collect()))?;
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "id,title,rating\n\
+         1,Ready Lets Go,4\n\
+         2,Music Is Math,\n\
+         3,Come Together,5\n\
+         4,Windowlicker,\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn columnar_round_trip() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let out = export::<Item>(&conn, &Export::new(Format::Columnar).row_group_size(3))?;
+    let items = Item::read_all(&conn)?;
+
+    let groups = ColumnarReader::new(out.as_slice())?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(groups.iter().map(|g| g.rows).collect::<Vec<_>>(), [3, 1]);
+    assert_eq!(
+        groups[1].column("title"),
+        Some(&[Value::from("Windowlicker")][..])
+    );
+    assert_eq!(groups[1].column("rating"), None);
+    assert_eq!(
+        groups[0].column("rating"),
+        Some(&[Value::from("4"), Value::Null, Value::from("5")][..])
+    );
+    assert_eq!(
+        groups[0].column("path").unwrap()[2],
+        Value::Bytes(path::to_bytes(&items[2].path))
+    );
+    assert_eq!(
+        groups[0].column("length").unwrap()[0],
+        Value::Real(items[0].length)
+    );
+
+    match ColumnarReader::new(&out[..out.len() - 3])?.last() {
+        Some(Err(Error::Export(_))) => {}
+        other => panic!("{:?}", other),
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn csv_keeps_path_bytes() -> Result<()> {
+    let latin1 = b"/music/Caf\xe9.flac".to_vec();
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute("UPDATE items SET path = ? WHERE id = 1", &[&latin1])?;
+    let columns = vec!["id".to_string(), "path".to_string()];
+    let out = export::<Item>(&conn, &Export::new(Format::Csv).columns(columns))?;
+
+    let mut expected = b"id,path\n1,".to_vec();
+    expected.extend_from_slice(&latin1);
+    expected.push(b'\n');
+    assert!(out.starts_with(&expected));
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::filter::{Condition, Entity, Filter};
+
+use super::*;
+
+/// Check that reading `filter` from the database gives the same entities,
+/// in the same order, as applying it to all of them in memory.
+fn assert_parity<T: Entity + Paged + PartialEq + std::fmt::Debug>(
+    conn: &Connection,
+    filter: &Filter,
+    read_all: fn(&Connection) -> Result<Vec<T>>,
+    read_filtered: fn(&Connection, &Filter) -> Result<Vec<T>>,
+) -> Result<Vec<u32>> {
+    let expected = filter.apply(read_all(conn)?);
+    let actual = read_filtered(conn, filter)?;
+    assert_eq!(actual, expected, "{filter:?}");
+    Ok(actual.iter().map(Paged::id).collect())
+}
+
+fn items(conn: &Connection, filter: &Filter) -> Result<Vec<u32>> {
+    assert_parity(conn, filter, Item::read_all, Item::read_filtered)
+}
+
+fn albums(conn: &Connection, filter: &Filter) -> Result<Vec<u32>> {
+    assert_parity(conn, filter, Album::read_all, Album::read_filtered)
+}
+
+#[test]
+fn field_conditions() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let with = |c| Filter::new().with(c);
+
+    assert_eq!(items(&conn, &Filter::new())?, [1, 2, 3, 4]);
+    assert_eq!(items(&conn, &with(Condition::is("year", 2002)))?, [1, 2]);
+    assert_eq!(items(&conn, &with(Condition::is("year", "1969")))?, [3]);
+    assert_eq!(items(&conn, &with(Condition::is("length", 59.8)))?, [1]);
+    assert_eq!(
+        items(&conn, &with(Condition::is("album_id", Value::Null)))?,
+        [4]
+    );
+    assert_eq!(
+        items(&conn, &with(Condition::contains("artist", "BOARDS")))?,
+        [1, 2]
+    );
+    assert!(items(&conn, &with(Condition::contains("title", "50%")))?.is_empty());
+    assert_eq!(
+        items(&conn, &with(Condition::contains("path", "/geogaddi/")))?,
+        [1, 2]
+    );
+    assert_eq!(
+        items(
+            &conn,
+            &with(Condition::range("length", Some(250.0), Some(330.0)))
+        )?,
+        [2, 3]
+    );
+    assert_eq!(
+        items(&conn, &with(Condition::range("year", None, Some(2000.0))))?,
+        [3, 4]
+    );
+    assert_eq!(
+        items(&conn, &with(!Condition::contains("genre", "electronic")))?,
+        [3]
+    );
+    assert_eq!(
+        items(
+            &conn,
+            &with(Condition::Any(vec![
+                Condition::is("format", "MP3"),
+                Condition::is("track", 2),
+            ]))
+        )?,
+        [2, 3, 4]
+    );
+    assert_eq!(
+        albums(&conn, &with(Condition::contains("album", "road")))?,
+        [2]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn flexible_attribute_conditions() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let with = |c| Filter::new().with(c);
+
+    assert_eq!(items(&conn, &with(Condition::is("rating", 4)))?, [1]);
+    assert_eq!(
+        items(&conn, &with(Condition::range("rating", Some(4.5), None)))?,
+        [3]
+    );
+    assert_eq!(
+        items(&conn, &with(Condition::is("rating", Value::Null)))?,
+        [2, 4]
+    );
+    assert_eq!(items(&conn, &with(!Condition::is("rating", 4)))?, [2, 3, 4]);
+    assert_eq!(
+        items(
+            &conn,
+            &with(Condition::range("rating", Some(1.0), None)).with(Condition::range(
+                "play_count",
+                Some(10.0),
+                None
+            ))
+        )?,
+        [1]
+    );
+    assert_eq!(
+        albums(&conn, &with(Condition::contains("mood", "haz")))?,
+        [1]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_columns_take_defaults() -> Result<()> {
+    let conn = fixture(BEETS_1_3_17)?;
+    let with = |c| Filter::new().with(c);
+
+    assert_eq!(items(&conn, &with(Condition::is("lyricist", "")))?, [1, 2]);
+    assert_eq!(
+        items(&conn, &with(Condition::is("r128_track_gain", 0)))?,
+        [1, 2]
+    );
+    assert_eq!(
+        items(&conn, &Filter::new().sort_by("lyricist", false))?,
+        [1, 2]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn sort_limit_offset() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    assert_eq!(
+        items(&conn, &Filter::new().sort_by("year", true))?,
+        [3, 4, 1, 2]
+    );
+    assert_eq!(
+        items(
+            &conn,
+            &Filter::new()
+                .sort_by("genre", false)
+                .sort_by("track", false)
+        )?,
+        [3, 2, 1, 4]
+    );
+    assert_eq!(
+        items(&conn, &Filter::new().sort_by("rating", false))?,
+        [3, 1, 2, 4]
+    );
+    assert_eq!(
+        items(&conn, &Filter::new().sort_by("length", true).limit(2))?,
+        [1, 3]
+    );
+    assert_eq!(items(&conn, &Filter::new().offset(3))?, [4]);
+    assert_eq!(
+        items(
+            &conn,
+            &Filter::new().sort_by("title", true).offset(1).limit(2)
+        )?,
+        [2, 1]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use super::*;
+
+#[test]
+fn iter_matches_read_all() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    let items = Item::iter(&conn).collect::<Result<Vec<_>>>()?;
+    assert_eq!(items, Item::read_all(&conn)?);
+
+    let albums = Album::iter(&conn).collect::<Result<Vec<_>>>()?;
+    assert_eq!(albums, Album::read_all(&conn)?);
+
+    Ok(())
+}
+
+#[test]
+fn pages_are_keyed_by_id() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    let pages = Item::pages(&conn, 3)
+        .map(|page| Ok(page?.iter().map(|item| item.id).collect()))
+        .collect::<Result<Vec<Vec<_>>>>()?;
+    assert_eq!(pages, vec![vec![1, 2, 3], vec![4]]);
+
+    let pages = Item::pages(&conn, 2).after(1).collect::<Result<Vec<_>>>()?;
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0][0].id, 2);
+
+    assert!(Item::pages(&conn, 2).after(4).next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn pages_include_flexible_attributes() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    let page = Item::read_page(&conn, Some(2), 1)?;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].id, 3);
+    assert_eq!(page[0].flex.get("rating").map(String::as_str), Some("5"));
+    assert!(!page[0].flex.contains_key("play_count"));
+
+    Ok(())
+}
+
+#[test]
+fn iter_stops_after_error() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch("UPDATE items SET track = 'one' WHERE id = 3;")?;
+
+    let mut pages = Item::pages(&conn, 2);
+    assert_eq!(pages.next().transpose()?.map(|page| page.len()), Some(2));
+    assert!(pages.next().is_some_and(|page| page.is_err()));
+    assert!(pages.next().is_none());
+
+    let mut items = Item::iter(&conn);
+    assert!(items.next().is_some_and(|item| item.is_err()));
+    assert!(items.next().is_none());
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+fn ids<'l>(items: impl Iterator<Item = ItemRef<'l>>) -> Vec<u32> {
+    items.map(|item| item.id).collect()
+}
+
+#[test]
+fn albums_and_items_are_linked() -> Result<()> {
+    let library = Library::read(&fixture(BEETS_1_4_7)?)?;
+    assert_eq!((library.album_count(), library.item_count()), (2, 4));
+
+    let geogaddi = library.album(1).unwrap();
+    assert_eq!(geogaddi.album, "Geogaddi");
+    assert_eq!(ids(geogaddi.items()), [1, 2]);
+
+    let item = library.item(3).unwrap();
+    assert_eq!(item.album().map(|album| album.id), Some(2));
+    assert!(!item.is_singleton());
+
+    assert_eq!(library.album(9).map(|album| album.id), None);
+    Ok(())
+}
+
+#[test]
+fn album_items_are_sorted_by_disc_then_track() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch(
+        "UPDATE items SET disc = 2, track = 1 WHERE id = 1;
+         INSERT INTO items (id, path, album_id, title, disc, track)
+         VALUES (5, CAST('/music/Boards of Canada/Geogaddi/1-01 Intro.flac' AS BLOB), 1,
+                 'Intro', 1, 1);",
+    )?;
+
+    let library = Library::read(&conn)?;
+    assert_eq!(ids(library.album(1).unwrap().items()), [5, 2, 1]);
+    Ok(())
+}
+
+#[test]
+fn singletons() -> Result<()> {
+    let library = Library::read(&fixture(BEETS_1_4_7)?)?;
+    let singletons = library.singletons().collect::<Vec<_>>();
+
+    assert_eq!(ids(singletons.iter().copied()), [4]);
+    assert!(singletons[0].album().is_none());
+    Ok(())
+}
@@ -1,9 +1,66 @@
 #![cfg(test)]
 
-use rusqlite::{Connection, OpenFlags, Result};
+#[cfg(feature = "sqlite")]
+use rusqlite::{Connection, OpenFlags};
 
 use super::*;
 
+type Result<T> = std::result::Result<T, Error>;
+
+// Everything but the in-memory backend needs `SQLite` to read the fixtures.
+mod backend;
+#[cfg(feature = "sqlite")]
+mod changes;
+#[cfg(feature = "sqlite")]
+mod check;
+#[cfg(feature = "sqlite")]
+mod config;
+#[cfg(feature = "sqlite")]
+mod duplicates;
+#[cfg(feature = "sqlite")]
+mod errors;
+#[cfg(feature = "export")]
+mod export;
+#[cfg(feature = "sqlite")]
+mod filter;
+#[cfg(feature = "sqlite")]
+mod iter;
+#[cfg(feature = "sqlite")]
+mod library;
+#[cfg(feature = "sqlite")]
+mod multi;
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "sqlite")]
+mod schema;
+#[cfg(feature = "sqlite")]
+mod stats;
+#[cfg(feature = "sqlite")]
+mod template;
+#[cfg(feature = "sqlite")]
+mod values;
+#[cfg(all(feature = "watch", target_os = "linux"))]
+mod watch;
+#[cfg(feature = "sqlite")]
+mod write;
+
+#[cfg(feature = "sqlite")]
+fn fixture(sql: &str) -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(sql)?;
+    Ok(conn)
+}
+
+#[cfg(feature = "sqlite")]
+const BEETS_1_3_17: &str = include_str!("../../tests/fixtures/beets-1.3.17.sql");
+#[cfg(feature = "sqlite")]
+const BEETS_1_4_7: &str = include_str!("../../tests/fixtures/beets-1.4.7.sql");
+#[cfg(feature = "sqlite")]
+const BEETS_1_4_7_MIGRATED: &str = include_str!("../../tests/fixtures/beets-1.4.7-migrated.sql");
+#[cfg(feature = "sqlite")]
+const BEETS_2_0_0: &str = include_str!("../../tests/fixtures/beets-2.0.0.sql");
+
+#[cfg(feature = "sqlite")]
 #[test]
 fn read_all_albums() -> Result<()> {
     let conn = Connection::open_with_flags("tests/test.db", OpenFlags::SQLITE_OPEN_READ_ONLY)?;
@@ -11,9 +68,74 @@ fn read_all_albums() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "sqlite")]
 #[test]
 fn read_all_tracks() -> Result<()> {
     let conn = Connection::open_with_flags("tests/test.db", OpenFlags::SQLITE_OPEN_READ_ONLY)?;
     Item::read_all(&conn)?;
     Ok(())
 }
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn read_flexible_attributes() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+
+    let albums = Album::read_all(&conn)?;
+    assert_eq!(albums[0].flex.get("mood").map(String::as_str), Some("hazy"));
+    assert_eq!(albums[1].flex.len(), 1);
+
+    let items = Item::read_all(&conn)?;
+    assert_eq!(items[0].flex.get("rating").map(String::as_str), Some("4"));
+    assert_eq!(
+        items[0].flex.get("play_count").map(String::as_str),
+        Some("12")
+    );
+    assert_eq!(items[2].flex.get("rating").map(String::as_str), Some("5"));
+    assert!(items[3].flex.is_empty());
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn serialize_flexible_attributes() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let item = Item::read_all(&conn)?.remove(0);
+
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(json["title"], "Ready Lets Go");
+    assert_eq!(json["rating"], "4");
+    assert_eq!(json["play_count"], "12");
+
+    let back: Item = serde_json::from_value(json).unwrap();
+    assert_eq!(back.flex, item.flex);
+
+    Ok(())
+}
+
+#[cfg(all(unix, feature = "sqlite"))]
+#[test]
+fn non_utf8_paths_round_trip() -> Result<()> {
+    // "/music/Café.flac", with the "é" in Latin-1.
+    let latin1 = b"/music/Caf\xe9.flac".to_vec();
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute("UPDATE items SET path = ? WHERE id = 1", &[&latin1])?;
+    let items = Item::read_all(&conn)?;
+
+    assert_eq!(path::to_bytes(&items[0].path), latin1);
+    assert_eq!(items[0].path_display(), "/music/Caf\u{fffd}.flac");
+
+    let json = serde_json::to_value(&items).unwrap();
+    assert_eq!(json[0]["path"]["bytes"][10], 0xe9);
+    assert_eq!(
+        json[1]["path"],
+        "/music/Boards of Canada/Geogaddi/02 Music Is Math.flac"
+    );
+
+    let back: Vec<Item> = serde_json::from_value(json).unwrap();
+    assert_eq!(back[0].path, items[0].path);
+    assert_eq!(back[1].path, items[1].path);
+
+    Ok(())
+}
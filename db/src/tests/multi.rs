@@ -0,0 +1,93 @@
+use super::*;
+use crate::filter::Condition;
+use crate::multi::Separators;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn multi_valued_columns() -> Result<()> {
+    let conn = fixture(BEETS_2_0_0)?;
+    let items = Item::read_all(&conn)?;
+    let separators = Separators::default();
+
+    let collaborators = strings(&["Daft Punk", "Pharrell Williams", "Nile Rodgers"]);
+    assert_eq!(items[1].artists, collaborators);
+    assert_eq!(items[1].all_artists(&separators), collaborators);
+    assert_eq!(items[0].all_artists(&separators), ["Daft Punk"]);
+    assert_eq!(Album::read_all(&conn)?[0].albumartists, ["Daft Punk"]);
+
+    let filter = Filter::new().with(Condition::contains("artists", "pharrell"));
+    let ids = Item::read_filtered(&conn, &filter)?
+        .iter()
+        .map(|item| item.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [2]);
+    assert_eq!(filter.apply(items), Item::read_filtered(&conn, &filter)?);
+
+    let library = Library::read(&conn)?;
+    assert!(library.artists(&separators).contains("Nile Rodgers"));
+    let ids = library
+        .items_by_artist("Pharrell Williams", &separators)
+        .map(|item| item.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [2]);
+    Ok(())
+}
+
+#[test]
+fn legacy_fields_are_split() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch(
+        "UPDATE items SET artist = 'Daft Punk feat. Pharrell Williams & Nile Rodgers',
+                          genre = 'Disco, Funk, Disco'
+         WHERE id = 1",
+    )?;
+    let item = Item::read_all(&conn)?.remove(0);
+    assert!(item.artists.is_empty());
+
+    let separators = Separators::default();
+    assert_eq!(
+        item.all_artists(&separators),
+        ["Daft Punk", "Pharrell Williams & Nile Rodgers"]
+    );
+    assert_eq!(item.all_genres(&separators), ["Disco", "Funk"]);
+    assert_eq!(
+        item.all_artists(&Separators::default().with_conjunctions()),
+        ["Daft Punk", "Pharrell Williams", "Nile Rodgers"]
+    );
+
+    assert_eq!(
+        item.all_artists(&Separators::none()),
+        [item.artist.as_str()]
+    );
+    let separators = Separators {
+        artist: strings(&[" & "]),
+        ..Separators::none()
+    };
+    assert_eq!(
+        item.all_artists(&separators),
+        ["Daft Punk feat. Pharrell Williams", "Nile Rodgers"]
+    );
+    assert_eq!(item.all_genres(&separators), ["Disco, Funk, Disco"]);
+    Ok(())
+}
+
+#[test]
+fn names_with_conjunctions_are_kept() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch(
+        "UPDATE items SET artist = 'Earth, Wind & Fire' WHERE id = 1;
+         UPDATE items SET artist = 'Simon & Garfunkel feat. Paul Simon' WHERE id = 2",
+    )?;
+    let items = Item::read_all(&conn)?;
+
+    let separators = Separators::default();
+    assert_eq!(items[0].all_artists(&separators), ["Earth, Wind & Fire"]);
+    assert_eq!(
+        items[1].all_artists(&separators),
+        ["Simon & Garfunkel", "Paul Simon"]
+    );
+    Ok(())
+}
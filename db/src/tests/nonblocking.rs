@@ -0,0 +1,52 @@
+use std::env;
+
+use futures::{Future, Stream};
+
+use super::*;
+use crate::nonblocking::Database;
+
+/// Copy the 1.4.7 fixture to a fresh file named after the test.
+fn database(name: &str) -> Result<(PathBuf, Database)> {
+    let path = env::temp_dir().join(format!("beet_db-nonblocking-{name}.db"));
+    let _ = std::fs::remove_file(&path);
+    Connection::open(&path)?.execute_batch(BEETS_1_4_7)?;
+    Ok((path.clone(), Database::new(path)))
+}
+
+#[test]
+fn futures_match_read_all() -> Result<()> {
+    let (_, db) = database("futures")?;
+    let (albums, items) = db.read_all().wait()?;
+    assert_eq!((albums.len(), items.len()), (2, 4));
+
+    let library = db.library().wait()?;
+    assert_eq!(library.album(1).unwrap().items().count(), 2);
+
+    let titles = db
+        .run(|c| Item::read_changed_since(c, 1_548_000_000.0))
+        .wait()?
+        .into_iter()
+        .map(|item| item.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Come Together", "Windowlicker"]);
+    Ok(())
+}
+
+#[test]
+fn streams_match_read_all() -> Result<()> {
+    let (path, db) = database("streams")?;
+    let conn = open(path)?;
+
+    assert_eq!(db.items().collect().wait()?, Item::read_all(&conn)?);
+    assert_eq!(db.albums().collect().wait()?, Album::read_all(&conn)?);
+    Ok(())
+}
+
+#[test]
+fn stream_reports_errors() {
+    let db = Database::new("tests/no-such.db");
+    match db.items().collect().wait() {
+        Err(Error::NotFound(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
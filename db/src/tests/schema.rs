@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use super::*;
+
+#[test]
+fn every_version_reads() -> Result<()> {
+    for sql in &[BEETS_1_3_17, BEETS_1_4_7, BEETS_1_4_7_MIGRATED, BEETS_2_0_0] {
+        let conn = fixture(sql)?;
+        assert!(!Album::read_all(&conn)?.is_empty());
+        assert!(!Item::read_all(&conn)?.is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn missing_columns_take_defaults() -> Result<()> {
+    let conn = fixture(BEETS_1_3_17)?;
+
+    let album = Album::read_all(&conn)?.remove(0);
+    assert_eq!(album.album, "Dummy");
+    assert_eq!(album.r128_album_gain, 0);
+    assert!(album.extra.is_empty());
+
+    let item = Item::read_all(&conn)?.remove(0);
+    assert_eq!(item.title, "Mysterons");
+    assert_eq!(item.composer, "Geoff Barrow");
+    assert_eq!(item.lyricist, "");
+    assert_eq!(item.arranger, "");
+    assert_eq!(item.r128_track_gain, 0);
+    assert_eq!(item.year, 1994);
+    assert!((item.length - 302.3).abs() < f64::EPSILON);
+    assert!(item.extra.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn appended_columns_bind_by_name() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7_MIGRATED)?;
+    let items = Item::read_all(&conn)?;
+
+    let old = &items[0];
+    assert_eq!(
+        old.path,
+        Path::new("/music/Portishead/Dummy/01 Mysterons.mp3")
+    );
+    assert_eq!(old.lyricist, "");
+    assert_eq!(old.r128_track_gain, 0);
+    assert!((old.mtime - 1_420_070_400.0).abs() < f64::EPSILON);
+
+    let new = &items[2];
+    assert_eq!(new.title, "Strangers");
+    assert_eq!(new.lyricist, "Beth Gibbons");
+    assert_eq!(new.composer_sort, "Barrow, Geoff");
+    assert!((new.added - 1_546_300_800.0).abs() < f64::EPSILON);
+    assert_eq!(new.flex.len(), 0);
+    assert_eq!(items[1].flex.get("rating").map(String::as_str), Some("5"));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_columns_are_kept() -> Result<()> {
+    let conn = fixture(BEETS_2_0_0)?;
+
+    let album = Album::read_all(&conn)?.remove(0);
+    assert_eq!(album.album, "Random Access Memories");
+    assert_eq!(album.year, 2013);
+    assert_eq!(album.extra.get("style").map(String::as_str), Some("Disco"));
+    assert_eq!(
+        album.extra.get("discogs_albumid").map(String::as_str),
+        Some("0")
+    );
+
+    let items = Item::read_all(&conn)?;
+    assert_eq!(items[0].r128_track_gain, 0);
+    assert_eq!(items[1].title, "Get Lucky");
+    assert_eq!(items[1].track, 8);
+    assert_eq!(items[1].r128_track_gain, 1280);
+    assert_eq!(items[1].format, "FLAC");
+    assert_eq!(
+        items[1].extra.get("bitrate_mode").map(String::as_str),
+        Some("")
+    );
+    assert!(!items[1].extra.contains_key("artists"));
+    assert!(!items[1].extra.contains_key("title"));
+    assert!(!items[1].extra.contains_key("rating"));
+
+    Ok(())
+}
+
+#[test]
+fn from_row_matches_read_all() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7_MIGRATED)?;
+    let mut stmt = conn.prepare("SELECT * FROM items")?;
+    let rows = stmt.query_and_then(NO_PARAMS, Item::from_row)?;
+    let by_name = rows.collect::<Result<Vec<_>>>()?;
+
+    let mut by_position = Item::read_all(&conn)?;
+    for item in &mut by_position {
+        item.flex.clear();
+    }
+    assert_eq!(by_name, by_position);
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::env;
+use std::fs;
+
+use super::*;
+use crate::multi::Separators;
+use crate::stats::{Stats, Tally};
+
+fn counts<K: Clone>(tallies: &BTreeMap<K, Tally>) -> Vec<(K, usize)> {
+    tallies
+        .iter()
+        .map(|(key, tally)| (key.clone(), tally.items))
+        .collect()
+}
+
+#[test]
+fn aggregates() -> Result<()> {
+    let library = Library::read(&fixture(BEETS_1_4_7)?)?;
+    let stats = Stats::new(&library, &Separators::default());
+
+    assert_eq!((stats.albums, stats.items), (2, 4));
+    assert_eq!((stats.artists, stats.albumartists), (3, 2));
+    assert!((stats.duration - 1007.2).abs() < 1e-9);
+    assert_eq!(stats.size, 67_013_625);
+
+    assert_eq!(
+        counts(&stats.formats),
+        [("FLAC".to_string(), 2), ("MP3".to_string(), 2)]
+    );
+    assert!((stats.formats["FLAC"].duration - 381.3).abs() < 1e-9);
+    assert_eq!(
+        counts(&stats.genres),
+        [("Electronic".to_string(), 3), ("Rock".to_string(), 1)]
+    );
+    assert_eq!(counts(&stats.decades), [(1960, 1), (1990, 1), (2000, 2)]);
+    assert!(stats.labels.is_empty() && stats.countries.is_empty());
+
+    let histogram = |map: &BTreeMap<u32, usize>| map.clone().into_iter().collect::<Vec<_>>();
+    assert_eq!(
+        histogram(&stats.bitrates),
+        [(256_000, 1), (320_000, 1), (896_000, 2)]
+    );
+    assert_eq!(histogram(&stats.samplerates), [(44_100, 4)]);
+    assert_eq!(
+        stats.top_artists,
+        [
+            ("Boards of Canada".to_string(), 2),
+            ("Aphex Twin".to_string(), 1),
+            ("The Beatles".to_string(), 1),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn unknown_rates_are_left_out() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut items = Item::read_all(&conn)?;
+    // A file that beets could not read the audio properties of.
+    let mut unread = items[3].clone();
+    unread.id = 5;
+    unread.bitrate = 0;
+    unread.samplerate = 0;
+    items.push(unread);
+    let library = Library::new(Album::read_all(&conn)?, items);
+    let stats = Stats::new(&library, &Separators::default());
+
+    assert_eq!(stats.items, 5);
+    assert_eq!(stats.bitrates.values().sum::<usize>(), 4);
+    assert!(!stats.bitrates.contains_key(&0));
+    assert_eq!(
+        stats.samplerates.into_iter().collect::<Vec<_>>(),
+        [(44_100, 4)]
+    );
+    Ok(())
+}
+
+#[test]
+fn exact_sizes() -> Result<()> {
+    let path = env::temp_dir().join("beet_db-stats.mp3");
+    fs::write(&path, b"ID3").unwrap();
+
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut items = Item::read_all(&conn)?;
+    items[3].path = path;
+    let library = Library::new(Album::read_all(&conn)?, items);
+
+    let estimated = Stats::new(&library, &Separators::default()).size;
+    let exact = Stats::exact(&library, &Separators::default()).size;
+    assert_eq!(exact, estimated - 11_712_000 + 3);
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use super::*;
+use crate::template::Template;
+
+fn library() -> Result<Library> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut albums = Album::read_all(&conn)?;
+    let mut items = Item::read_all(&conn)?;
+
+    // A reissue of Abbey Road, on the same label.
+    let mut reissue = albums[1].clone();
+    reissue.id = 3;
+    reissue.year = 2019;
+    albums.push(reissue);
+    let mut track = items[2].clone();
+    track.id = 5;
+    track.album_id = Some(3);
+    items.push(track);
+
+    Ok(Library::new(albums, items))
+}
+
+fn item(library: &Library, id: u32, template: &str) -> String {
+    Template::parse(template).evaluate_item(library.item(id).unwrap())
+}
+
+#[test]
+fn default_path_formats() -> Result<()> {
+    let library = library()?;
+    let default = "$albumartist/$album%aunique{}/$track $title";
+    assert_eq!(
+        item(&library, 1, default),
+        "Boards of Canada/Geogaddi/01 Ready Lets Go"
+    );
+    assert_eq!(
+        item(&library, 3, default),
+        "The Beatles/Abbey Road [1969]/01 Come Together"
+    );
+    assert_eq!(
+        item(&library, 5, default),
+        "The Beatles/Abbey Road [2019]/01 Come Together"
+    );
+    assert_eq!(
+        item(&library, 4, "Non-Album/$artist/$title"),
+        "Non-Album/Aphex Twin/Windowlicker"
+    );
+
+    let album = library.album(1).unwrap();
+    assert_eq!(
+        Template::parse("$albumartist - $album ($year)").evaluate_album(album),
+        "Boards of Canada - Geogaddi (2002)"
+    );
+    Ok(())
+}
+
+#[test]
+fn functions() -> Result<()> {
+    let library = library()?;
+    let cases = &[
+        ("%upper{$title}", "READY LETS GO"),
+        ("%lower{$albumartist}", "boards of canada"),
+        ("%capitalize{hELLO world}", "Hello world"),
+        (
+            "%title{the dark  side of the moon}",
+            "The Dark Side Of The Moon",
+        ),
+        ("%left{$album,3}", "Geo"),
+        ("%right{$album,4}", "addi"),
+        ("%if{$comp,Compilations,$albumartist}", "Boards of Canada"),
+        ("%if{$track,Track $track}", "Track 01"),
+        ("%if{0,yes,no}", "no"),
+        ("%ifdef{rating,rated $rating,unrated}", "rated 4"),
+        ("%ifdef{rating}", "4"),
+        ("%ifdef{mood,$mood,no}", "hazy"),
+        ("%ifdef{tempo,yes,no}", "no"),
+        ("%first{Pop; Rock; Classical}", "Pop"),
+        ("%first{Pop; Rock; Classical,2,1}", "Rock; Classical"),
+        ("%first{Pop/Rock/Jazz,2,0,/, & }", "Pop & Rock"),
+        ("$album%aunique{}", "Geogaddi"),
+    ];
+    for (template, expected) in cases {
+        assert_eq!(item(&library, 1, template), *expected, "{template}");
+    }
+
+    let cases = &[
+        ("%aunique{albumartist album,year,()}", " (1969)"),
+        ("%aunique{albumartist album,label}", " [2]"),
+        ("%aunique{albumartist album,label,}", " 2"),
+        ("%aunique{album,albumtype}", " [2]"),
+    ];
+    for (template, expected) in cases {
+        assert_eq!(item(&library, 3, template), *expected, "{template}");
+    }
+    Ok(())
+}
+
+#[test]
+fn dates_in_local_time() -> Result<()> {
+    let library = library()?;
+    let added = |id, offset| {
+        Template::parse("$added")
+            .with_utc_offset(offset)
+            .evaluate_item(library.item(id).unwrap())
+    };
+    // Item 1 was added at midnight UTC on 2019-01-01, which was still the
+    // evening before five hours behind.
+    assert_eq!(added(1, -5 * 60 * 60), "2018-12-31 19:00:00");
+    assert_eq!(added(3, 60 * 60), "2019-02-01 01:00:00");
+
+    let album = Template::parse("$year-$added").with_utc_offset(-60 * 60);
+    assert_eq!(
+        album.evaluate_album(library.album(1).unwrap()),
+        "2002-2018-12-31 23:00:00"
+    );
+    Ok(())
+}
+
+#[test]
+fn formatting() -> Result<()> {
+    let library = library()?;
+    let template = Template::parse("$length $bitrate $samplerate $comp $added");
+    assert_eq!(
+        template
+            .with_utc_offset(0)
+            .evaluate_item(library.item(1).unwrap()),
+        "0:59 900kbps 44kHz False 2019-01-01 00:00:00"
+    );
+
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut items = Item::read_all(&conn)?;
+    items[0].artist = String::new();
+    items[0].title = "AC/DC".to_string();
+    let library = Library::new(Album::read_all(&conn)?, items);
+    assert_eq!(
+        item(&library, 1, "$artist/$title"),
+        "Boards of Canada/AC_DC"
+    );
+    Ok(())
+}
+
+#[test]
+fn malformed_templates() -> Result<()> {
+    let library = library()?;
+    let cases = &[
+        ("$$5 $%d $, $}", "$5 %d , }"),
+        ("${title}s", "Ready Lets Gos"),
+        ("$nope ${nope} ${} $", "$nope ${nope} ${} $"),
+        ("100% %nope{$track} %{x}", "100% %nope{$track} %{x}"),
+        ("%lower{ABC", "%lower{ABC"),
+        ("%lower", "%lower"),
+        ("a}b, {c}", "a}b, {c}"),
+        ("%left{$title,x}", "<not a number: \"x\">"),
+        ("%lower{a,b}", "<%lower takes 1 to 1 arguments, not 2>"),
+    ];
+    for (template, expected) in cases {
+        assert_eq!(item(&library, 1, template), *expected, "{template}");
+    }
+
+    let min = i64::MIN;
+    assert_eq!(
+        item(&library, 1, &format!("%right{{$title,{min}}}")),
+        format!("<out of range: {min}>")
+    );
+    assert_eq!(item(&library, 1, &format!("%left{{$title,{min}}}")), "");
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use super::*;
+
+#[test]
+fn partial_dates() {
+    let date = |y, m, d| PartialDate::new(y, m, d);
+
+    assert_eq!(date(0, 9, 26), None);
+    assert_eq!(date(1969, 0, 26).unwrap().to_string(), "1969");
+    assert_eq!(date(1969, 9, 0).unwrap().to_string(), "1969-09");
+    assert_eq!(date(1969, 9, 26).unwrap().to_string(), "1969-09-26");
+    assert_eq!(date(1969, 13, 1).unwrap().month, None);
+
+    assert!(date(1969, 0, 0) < date(1969, 9, 0));
+    assert!(date(1969, 9, 0) < date(1969, 9, 26));
+    assert!(date(1969, 9, 26) < date(1970, 0, 0));
+}
+
+#[test]
+fn typed_accessors() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    conn.execute_batch(
+        "UPDATE albums SET original_year = 1969 WHERE id = 2;
+         UPDATE items SET rg_track_gain = -6.5 WHERE id = 1;
+         UPDATE items SET r128_track_gain = -1280, r128_album_gain = 384 WHERE id = 3;",
+    )?;
+
+    let albums = Album::read_all(&conn)?;
+    assert_eq!(albums[1].date().unwrap().to_string(), "1969-09-26");
+    assert_eq!(albums[1].original_date().unwrap().to_string(), "1969");
+    assert_eq!(albums[0].original_date(), None);
+
+    let items = Item::read_all(&conn)?;
+    assert_eq!(items[0].duration(), Duration::from_millis(59_800));
+    assert_eq!(items[0].track_gain_db(), Some(-6.5));
+    assert_eq!(items[0].r128_track_gain_db(), None);
+    assert_eq!(items[2].r128_track_gain_db(), Some(-5.0));
+    assert_eq!(items[2].r128_album_gain_db(), Some(1.5));
+    assert_eq!(items[2].track_gain_db(), Some(0.0));
+    assert_eq!(items[3].album_gain_db(), None);
+
+    Ok(())
+}
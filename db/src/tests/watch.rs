@@ -0,0 +1,82 @@
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+use super::*;
+use crate::watch::{Diff, Watcher};
+
+#[test]
+fn diff_between_libraries() -> Result<()> {
+    let conn = fixture(BEETS_1_4_7)?;
+    let mut library = Library::read(&conn)?;
+    conn.execute_batch(
+        "UPDATE albums SET album = 'Geogaddi (Remastered)' WHERE id = 1;
+         DELETE FROM items WHERE id = 2;
+         INSERT INTO items (id, path, album_id, title, disc, track)
+         VALUES (5, CAST('/music/Boards of Canada/Geogaddi/02 Music Is Math.flac' AS BLOB), 1,
+                 'Music Is Math', 1, 2);",
+    )?;
+    let updated = Library::read(&conn)?;
+
+    let diff = Diff::between(&library, &updated);
+    assert!(diff.albums.added.is_empty() && diff.albums.removed.is_empty());
+    assert_eq!(
+        diff.albums.modified,
+        [updated.album(1).unwrap().as_album().clone()]
+    );
+    assert_eq!(
+        diff.items.added,
+        [updated.item(5).unwrap().as_item().clone()]
+    );
+    assert!(diff.items.modified.is_empty());
+    assert_eq!(diff.items.removed, [2]);
+    assert!(Diff::between(&updated, &updated).is_empty());
+
+    library.apply(&diff);
+    assert_eq!(library.album(1).unwrap().album, "Geogaddi (Remastered)");
+    let tracks = library
+        .album(1)
+        .unwrap()
+        .items()
+        .map(|i| i.id)
+        .collect::<Vec<_>>();
+    assert_eq!(tracks, [1, 5]);
+    Ok(())
+}
+
+#[test]
+fn watcher_reports_writes() -> Result<()> {
+    let path = env::temp_dir().join("beet_db-watch.db");
+    let _ = fs::remove_file(&path);
+    Connection::open(&path)?.execute_batch(BEETS_1_4_7)?;
+
+    let library = Library::open(&path)?;
+    let (watcher, changes) = Watcher::channel(&path, &library, Duration::from_millis(50))?;
+    let mut writer = Writer::open(&path)?;
+    // beets sets `mtime` when it writes the file's tags.
+    let changes_to_item = Changes::new()
+        .set("title", "Dig It".to_string())
+        .set("mtime", 1_600_000_000.0);
+    let item = writer.update_item(3, &changes_to_item)?;
+
+    let diff = changes
+        .recv_timeout(Duration::from_secs(10))
+        .expect("no change was reported")?;
+    assert_eq!(
+        diff.albums.modified,
+        [library.album(2).unwrap().as_album().clone()]
+    );
+    assert_eq!(diff.items.modified, [item]);
+    assert!(diff.items.added.is_empty() && diff.items.removed.is_empty());
+
+    writer
+        .connection()
+        .execute_batch("DELETE FROM items WHERE id = 4")?;
+    let diff = changes
+        .recv_timeout(Duration::from_secs(10))
+        .expect("no removal was reported")?;
+    assert!(diff.albums.is_empty());
+    assert_eq!(diff.items.removed, [4]);
+    drop(watcher);
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::env;
+use std::fs;
+
+use super::*;
+
+/// Copy the 1.4.7 fixture to a fresh file named after the test, since a
+/// writer can only be opened on a file.
+fn writable(name: &str) -> Result<(PathBuf, Writer)> {
+    writable_from(name, BEETS_1_4_7)
+}
+
+fn writable_from(name: &str, sql: &str) -> Result<(PathBuf, Writer)> {
+    let path = env::temp_dir().join(format!("beet_db-write-{name}.db"));
+    let _ = fs::remove_file(&path);
+    Connection::open(&path)?.execute_batch(sql)?;
+    let writer = Writer::open(&path)?;
+    Ok((path, writer))
+}
+
+#[test]
+fn update_item_fields_and_flex() -> Result<()> {
+    let (path, mut writer) = writable("item")?;
+    let changes = Changes::new()
+        .set("title", "Come Together (Remastered)".to_string())
+        .set("year", 2009)
+        .set_flex("rating", "3")
+        .set_flex("mood", "groovy");
+
+    let item = writer.update_item(3, &changes)?;
+    assert_eq!(item.title, "Come Together (Remastered)");
+    assert_eq!(item.year, 2009);
+    assert_eq!(item.flex["rating"], "3");
+    assert_eq!(item.flex["mood"], "groovy");
+
+    let stored = Item::read_all(&open(&path)?)?;
+    assert_eq!(stored[2], item);
+    assert_eq!(stored[0].flex["rating"], "4");
+    Ok(())
+}
+
+#[test]
+fn update_album_removes_flex() -> Result<()> {
+    let (_, mut writer) = writable("album")?;
+    let album = writer.update_album(1, &Changes::new().remove_flex("mood"))?;
+    assert!(album.flex.is_empty());
+    assert_eq!(album.album, "Geogaddi");
+    Ok(())
+}
+
+#[test]
+fn update_album_changes_its_items() -> Result<()> {
+    let (_, mut writer) = writable("album-items")?;
+    let changes = Changes::new()
+        .set("year", 2003)
+        .set("albumtype", "compilation".to_string())
+        .set_flex("mood", "sunny");
+    let album = writer.update_album(1, &changes)?;
+    assert_eq!(album.year, 2003);
+
+    let items = Item::read_all(writer.connection())?;
+    assert_eq!(items[0].year, 2003);
+    assert_eq!(items[1].year, 2003);
+    assert_eq!(items[2].year, 1969);
+    assert!(!items[0].flex.contains_key("mood"));
+    Ok(())
+}
+
+#[test]
+fn update_album_keeps_items_added() -> Result<()> {
+    let (_, mut writer) = writable("album-added")?;
+    let before = Item::read_all(writer.connection())?;
+    let album = writer.update_album(1, &Changes::new().set("added", 1_600_000_000.0))?;
+    assert!((album.added - 1_600_000_000.0).abs() < f64::EPSILON);
+
+    let after = Item::read_all(writer.connection())?;
+    assert_eq!(after, before);
+    Ok(())
+}
+
+#[test]
+fn changes_follow_the_schema() -> Result<()> {
+    let (_, mut writer) = writable_from("old-schema", BEETS_1_3_17)?;
+    let artists = Changes::new().set("artists", "Portishead".to_string());
+    match writer.update_item(1, &artists) {
+        Err(Error::UnknownField { field, .. }) => assert_eq!(field, "artists"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let (_, mut writer) = writable_from("new-schema", BEETS_2_0_0)?;
+    let mode = Changes::new().set("bitrate_mode", "cbr".to_string());
+    let item = writer.update_item(2, &mode)?;
+    assert_eq!(item.extra["bitrate_mode"], "cbr");
+    Ok(())
+}
+
+#[test]
+fn invalid_changes_are_rejected() -> Result<()> {
+    let (_, mut writer) = writable("invalid")?;
+
+    match writer.update_item(1, &Changes::new().set("titel", "Oops".to_string())) {
+        Err(Error::UnknownField { table, field }) => {
+            assert_eq!((table, &*field), ("items", "titel"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match writer.update_item(1, &Changes::new().set("id", 7)) {
+        Err(Error::ReadOnlyField { field, .. }) => assert_eq!(field, "id"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match writer.update_item(1, &Changes::new().set_flex("title", "Oops")) {
+        Err(Error::NotFlexible { field, .. }) => assert_eq!(field, "title"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match writer.update_album(9, &Changes::new().set("year", 2000)) {
+        Err(Error::NoSuchEntry { table, id }) => assert_eq!((table, id), ("albums", 9)),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn failed_update_is_rolled_back() -> Result<()> {
+    let (_, mut writer) = writable("rollback")?;
+    let changes = Changes::new()
+        .set("year", "nineteen sixty-nine".to_string())
+        .set_flex("rating", "1");
+
+    match writer.update_item(3, &changes) {
+        Err(Error::BadValue { column, .. }) => assert_eq!(column, "year"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let item = &Item::read_all(writer.connection())?[2];
+    assert_eq!(item.year, 1969);
+    assert_eq!(item.flex["rating"], "5");
+    Ok(())
+}
+
+#[test]
+fn open_stays_read_only() -> Result<()> {
+    let (path, _) = writable("read-only")?;
+    let conn = open(&path)?;
+    assert!(conn.execute_batch("UPDATE items SET year = 2000").is_err());
+    Ok(())
+}
@@ -0,0 +1,336 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{multi, path, Album, Item};
+
+/// How much louder the `ReplayGain` reference level (-18 LUFS) is than the
+/// EBU R128 one (-23 LUFS), in decibels.
+const R128_TO_REPLAYGAIN: f64 = 5.0;
+
+/// A release date, which may only be known to the year or month.
+///
+/// Dates order chronologically, with a less precise date coming before any
+/// more precise one within it (so 1969 < 1969-09 < 1969-09-26).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartialDate {
+    pub year: u16,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl PartialDate {
+    /// Build a date from the fields beets stores, where `0` means unknown.
+    ///
+    /// Returns `None` if the year is unknown. A day without a month, or a
+    /// value that is out of range, is treated as unknown.
+    #[must_use]
+    pub fn new(year: u16, month: u32, day: u32) -> Option<Self> {
+        if year == 0 {
+            return None;
+        }
+
+        let month = u8::try_from(month).ok().filter(|m| (1..=12).contains(m));
+        let day = u8::try_from(day)
+            .ok()
+            .filter(|d| month.is_some() && (1..=31).contains(d));
+
+        Some(Self { year, month, day })
+    }
+}
+
+impl fmt::Display for PartialDate {
+    /// Formats as `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{month:02}")?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "-{day:02}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a Q7.8 fixed-point R128 gain to decibels, where `0` means unset.
+fn r128_to_db(gain: i32) -> Option<f64> {
+    if gain == 0 {
+        None
+    } else {
+        Some(f64::from(gain) / 256.0)
+    }
+}
+
+/// The `ReplayGain` adjustment, or failing that the R128 one converted to the
+/// `ReplayGain` reference level.
+fn gain_db(replaygain: Option<f64>, r128: i32) -> Option<f64> {
+    replaygain.or_else(|| r128_to_db(r128).map(|db| db + R128_TO_REPLAYGAIN))
+}
+
+impl Album {
+    /// The release date of this album, if its year is known.
+    #[must_use]
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::new(self.year, self.month.into(), self.day.into())
+    }
+
+    /// The date of the original release of this album, if its year is known.
+    #[must_use]
+    pub fn original_date(&self) -> Option<PartialDate> {
+        PartialDate::new(
+            self.original_year,
+            self.original_month.into(),
+            self.original_day.into(),
+        )
+    }
+
+    /// The EBU R128 album gain in decibels, relative to -23 LUFS.
+    #[must_use]
+    pub fn r128_album_gain_db(&self) -> Option<f64> {
+        r128_to_db(self.r128_album_gain)
+    }
+
+    /// The album gain in decibels, relative to the `ReplayGain` reference
+    /// level. This is the `ReplayGain` value if there is one, or else the R128
+    /// value adjusted to the same reference.
+    #[must_use]
+    pub fn album_gain_db(&self) -> Option<f64> {
+        gain_db(self.rg_album_gain, self.r128_album_gain)
+    }
+}
+
+impl Item {
+    /// The release date of this item, if its year is known.
+    #[must_use]
+    pub fn date(&self) -> Option<PartialDate> {
+        PartialDate::new(self.year, self.month, self.day)
+    }
+
+    /// The date of the original release of this item, if its year is known.
+    #[must_use]
+    pub fn original_date(&self) -> Option<PartialDate> {
+        PartialDate::new(
+            self.original_year,
+            self.original_month.into(),
+            self.original_day.into(),
+        )
+    }
+
+    /// The length of the track. A negative or missing length is zero.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        if self.length.is_finite() && self.length > 0.0 {
+            Duration::from_secs_f64(self.length)
+        } else {
+            Duration::default()
+        }
+    }
+
+    /// The EBU R128 track gain in decibels, relative to -23 LUFS.
+    #[must_use]
+    pub fn r128_track_gain_db(&self) -> Option<f64> {
+        r128_to_db(self.r128_track_gain)
+    }
+
+    /// The EBU R128 album gain in decibels, relative to -23 LUFS.
+    #[must_use]
+    pub fn r128_album_gain_db(&self) -> Option<f64> {
+        r128_to_db(self.r128_album_gain)
+    }
+
+    /// The track gain in decibels, relative to the `ReplayGain` reference
+    /// level. This is the `ReplayGain` value if there is one, or else the R128
+    /// value adjusted to the same reference.
+    #[must_use]
+    pub fn track_gain_db(&self) -> Option<f64> {
+        gain_db(self.rg_track_gain, self.r128_track_gain)
+    }
+
+    /// The gain of this item's album in decibels, relative to the `ReplayGain`
+    /// reference level, as for [`track_gain_db`](#method.track_gain_db).
+    #[must_use]
+    pub fn album_gain_db(&self) -> Option<f64> {
+        gain_db(self.rg_album_gain, self.r128_album_gain)
+    }
+}
+
+/// The value of a field or flexible attribute, as `SQLite` would store it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    /// This value as text, as `SQLite` would cast it, or `None` if it is
+    /// `NULL`.
+    #[must_use]
+    pub fn as_text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Null => None,
+            Value::Integer(i) => Some(i.to_string().into()),
+            Value::Real(f) => Some(real_to_text(*f).into()),
+            Value::Text(s) => Some(s.as_str().into()),
+            Value::Bytes(b) => Some(String::from_utf8_lossy(b)),
+        }
+    }
+
+    /// This value as a number, as `SQLite` would cast it, or `None` if it is
+    /// `NULL`. Text is read up to the first character that cannot be part of
+    /// a number, so `"12 inch"` is `12.0` and `"none"` is `0.0`.
+    #[must_use]
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Value::Null => None,
+            #[allow(clippy::cast_precision_loss)]
+            Value::Integer(i) => Some(*i as f64),
+            Value::Real(f) => Some(*f),
+            Value::Text(_) | Value::Bytes(_) => self.as_text().map(|s| text_to_real(&s)),
+        }
+    }
+
+    /// Order values as `SQLite` does: `NULL` first, then numbers, then text,
+    /// then blobs.
+    #[must_use]
+    pub fn sql_cmp(&self, other: &Self) -> Ordering {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Null => 0,
+                Value::Integer(_) | Value::Real(_) => 1,
+                Value::Text(_) => 2,
+                Value::Bytes(_) => 3,
+            }
+        }
+
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            (a, b) if rank(a) == 1 && rank(b) == 1 => a
+                .as_real()
+                .partial_cmp(&b.as_real())
+                .unwrap_or(Ordering::Equal),
+            (a, b) => rank(a).cmp(&rank(b)),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Value::Integer(i)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self {
+        Value::Real(f)
+    }
+}
+
+/// Format a number as `SQLite` casts it to text: to 15 significant digits,
+/// and always with a decimal point.
+fn real_to_text(f: f64) -> String {
+    let rounded = format!("{f:.14e}").parse().unwrap_or(f);
+    if rounded.fract() == 0.0 && rounded.abs() < 1e15 {
+        format!("{rounded:.1}")
+    } else {
+        rounded.to_string()
+    }
+}
+
+/// Read the longest prefix of `s` that is a number, as `SQLite` casts text to
+/// a number, or `0.0` if there is none.
+fn text_to_real(s: &str) -> f64 {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+        .unwrap_or(s.len());
+    (0..=end)
+        .rev()
+        .find_map(|end| s[..end].parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// A type of field that can be read as a [`Value`](enum.Value.html).
+pub(crate) trait ToValue {
+    /// The SQL literal for the value that the field takes when its column is
+    /// `NULL` or missing.
+    const SQL_DEFAULT: &'static str;
+
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! integer_to_value {
+    ( $( $typ:ty ),* ) => {
+        $(
+            impl ToValue for $typ {
+                const SQL_DEFAULT: &'static str = "0";
+
+                fn to_value(&self) -> Value {
+                    Value::Integer(i64::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+integer_to_value!(bool, u8, u16, u32, i32);
+
+impl ToValue for f64 {
+    const SQL_DEFAULT: &'static str = "0.0";
+
+    fn to_value(&self) -> Value {
+        Value::Real(*self)
+    }
+}
+
+impl ToValue for String {
+    const SQL_DEFAULT: &'static str = "''";
+
+    fn to_value(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToValue for Vec<String> {
+    const SQL_DEFAULT: &'static str = "''";
+
+    fn to_value(&self) -> Value {
+        Value::Text(self.join(multi::SEPARATOR))
+    }
+}
+
+impl ToValue for PathBuf {
+    const SQL_DEFAULT: &'static str = "''";
+
+    fn to_value(&self) -> Value {
+        Value::Bytes(path::to_bytes(self))
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    const SQL_DEFAULT: &'static str = "NULL";
+
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, ToValue::to_value)
+    }
+}
@@ -0,0 +1,386 @@
+//! Watch a beets database for changes, e.g. while `beet import` is running.
+//!
+//! The directory holding the database is watched with inotify, for writes to
+//! the database and its write-ahead log. Once the writes have settled, the
+//! entries that were added or changed since the last read are read, along
+//! with the ids of those that were removed, and the difference is delivered
+//! to a callback or a channel.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use inotify::{Inotify, WatchMask};
+
+use super::{open, Album, Error, Item, Library};
+
+/// How often the watching thread checks whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How writes to a table changed its entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delta<T> {
+    /// The entries that were added, in order of id.
+    pub added: Vec<T>,
+    /// The entries that changed, as they are now, in order of id.
+    pub modified: Vec<T>,
+    /// The ids of the entries that were removed, in ascending order.
+    pub removed: Vec<u32>,
+}
+
+/// How writes to the database changed its albums and items.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    pub albums: Delta<Album>,
+    pub items: Delta<Item>,
+}
+
+impl<T> Default for Delta<T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl<T> Delta<T> {
+    /// Whether no entries were added, changed or removed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<T: Clone + PartialEq> Delta<T> {
+    /// Compare two snapshots of a table, each in order of id.
+    fn between<'a, O, N>(old: O, new: N, id: fn(&T) -> u32) -> Self
+    where
+        T: 'a,
+        O: Iterator<Item = &'a T>,
+        N: Iterator<Item = &'a T>,
+    {
+        let mut delta = Self::default();
+        let mut old = old.peekable();
+        let mut new = new.peekable();
+
+        loop {
+            match (old.peek(), new.peek()) {
+                (None, None) => break,
+                (Some(o), Some(n)) if id(o) == id(n) => {
+                    if o != n {
+                        delta.modified.push((*n).clone());
+                    }
+                    old.next();
+                    new.next();
+                }
+                (Some(o), Some(n)) if id(o) > id(n) => delta.added.extend(new.next().cloned()),
+                (Some(_), _) => delta.removed.extend(old.next().map(id)),
+                (None, Some(_)) => delta.added.extend(new.next().cloned()),
+            }
+        }
+
+        delta
+    }
+}
+
+impl<T> Delta<T> {
+    /// Sort entries that were read again into those that are new and those
+    /// that were already `known`.
+    fn split(changed: Vec<T>, removed: Vec<u32>, known: &BTreeSet<u32>, id: fn(&T) -> u32) -> Self {
+        let (modified, added) = changed
+            .into_iter()
+            .partition(|entry| known.contains(&id(entry)));
+        Self {
+            added,
+            modified,
+            removed,
+        }
+    }
+}
+
+impl Diff {
+    /// The difference between two snapshots of a library.
+    #[must_use]
+    pub fn between(old: &Library, new: &Library) -> Self {
+        Self {
+            albums: Delta::between(
+                old.albums().map(|a| a.as_album()),
+                new.albums().map(|a| a.as_album()),
+                |album| album.id,
+            ),
+            items: Delta::between(
+                old.items().map(|i| i.as_item()),
+                new.items().map(|i| i.as_item()),
+                |item| item.id,
+            ),
+        }
+    }
+
+    /// Whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.albums.is_empty() && self.items.is_empty()
+    }
+}
+
+/// Watches a database on a thread of its own, until it is dropped.
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Watch the database at `db_path`, calling `callback` with each change
+    /// to its albums and items since it was read into `library`, once there
+    /// have been no writes to it for `debounce`.
+    ///
+    /// Changes are found as by [`Item::read_changed_since`], counting from
+    /// the latest `mtime` or `added` seen so far, so changes that beets
+    /// makes to the database alone are missed. An album is reported as
+    /// modified when any of its items is.
+    ///
+    /// If the database cannot be read after a change, `callback` is given
+    /// the error, and the next change is read from where the last one that
+    /// could be read left off. If the database can no longer be watched,
+    /// `callback` is given that error and is not called again.
+    ///
+    /// [`Item::read_changed_since`]: ../struct.Item.html#method.read_changed_since
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory of the database cannot be watched.
+    pub fn new<P, F>(
+        db_path: P,
+        library: &Library,
+        debounce: Duration,
+        callback: F,
+    ) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+        F: FnMut(Result<Diff, Error>) + Send + 'static,
+    {
+        let db_path = db_path.into();
+
+        let dir = match db_path.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let io_error = |source| Error::Io {
+            path: dir.clone(),
+            source,
+        };
+        let mut inotify = Inotify::init().map_err(io_error)?;
+        inotify
+            .add_watch(
+                &dir,
+                WatchMask::MODIFY
+                    | WatchMask::CLOSE_WRITE
+                    | WatchMask::CREATE
+                    | WatchMask::DELETE
+                    | WatchMask::MOVED_TO,
+            )
+            .map_err(io_error)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let state = State {
+            inotify,
+            names: watched_names(&db_path),
+            dir,
+            db_path,
+            debounce,
+            stop: Arc::clone(&stop),
+            albums: library.albums().map(|album| album.id).collect(),
+            items: library.items().map(|item| item.id).collect(),
+            since: library
+                .albums()
+                .map(|album| album.added)
+                .chain(library.items().map(|item| item.mtime.max(item.added)))
+                .fold(0.0, f64::max),
+        };
+        let thread = thread::spawn(move || state.run(callback));
+
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Watch the database at `db_path`, sending each change to the returned
+    /// channel, as for [`new`](#method.new).
+    ///
+    /// # Errors
+    ///
+    /// See [`new`](#method.new).
+    pub fn channel<P: Into<PathBuf>>(
+        db_path: P,
+        library: &Library,
+        debounce: Duration,
+    ) -> Result<(Self, Receiver<Result<Diff, Error>>), Error> {
+        let (tx, rx) = mpsc::channel();
+        let watcher = Self::new(db_path, library, debounce, move |diff| {
+            // Nobody is listening if the receiver was dropped, but the
+            // watcher still stops when it is dropped itself.
+            let _ = tx.send(diff);
+        })?;
+        Ok((watcher, rx))
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The state of the watching thread.
+struct State {
+    inotify: Inotify,
+    /// The file names of the database and its write-ahead log.
+    names: Vec<OsString>,
+    dir: PathBuf,
+    db_path: PathBuf,
+    debounce: Duration,
+    stop: Arc<AtomicBool>,
+    /// The ids of the albums and items as they were last read.
+    albums: BTreeSet<u32>,
+    items: BTreeSet<u32>,
+    /// The latest `mtime` or `added` seen, in seconds since the Unix epoch.
+    since: f64,
+}
+
+impl State {
+    fn run<F: FnMut(Result<Diff, Error>)>(mut self, mut callback: F) {
+        let mut buffer = [0; 4096];
+
+        while !self.stop.load(Ordering::Relaxed) {
+            match self.settle(&mut buffer) {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(source) => {
+                    callback(Err(Error::Io {
+                        path: self.dir,
+                        source,
+                    }));
+                    return;
+                }
+            }
+
+            match self.read_changes() {
+                Ok(diff) if diff.is_empty() => {}
+                result => callback(result),
+            }
+        }
+    }
+
+    /// Read what changed since the last read, and remember it for the next.
+    fn read_changes(&mut self) -> Result<Diff, Error> {
+        let c = open(&self.db_path)?;
+        let albums = Album::read_changed_since(&c, self.since)?;
+        let items = Item::read_changed_since(&c, self.since)?;
+        let diff = Diff {
+            albums: Delta::split(
+                albums,
+                Album::read_removed(&c, self.albums.iter().copied())?,
+                &self.albums,
+                |album| album.id,
+            ),
+            items: Delta::split(
+                items,
+                Item::read_removed(&c, self.items.iter().copied())?,
+                &self.items,
+                |item| item.id,
+            ),
+        };
+
+        for id in &diff.albums.removed {
+            self.albums.remove(id);
+        }
+        for id in &diff.items.removed {
+            self.items.remove(id);
+        }
+        for album in &diff.albums.added {
+            self.albums.insert(album.id);
+            self.since = self.since.max(album.added);
+        }
+        for item in diff.items.added.iter().chain(&diff.items.modified) {
+            self.items.insert(item.id);
+            self.since = self.since.max(item.mtime.max(item.added));
+        }
+        Ok(diff)
+    }
+
+    /// Wait a little for the database to be written to, and if it is, until
+    /// there have been no more writes for the debounce period. Returns
+    /// whether it was written to.
+    fn settle(&mut self, buffer: &mut [u8]) -> io::Result<bool> {
+        if !(self.wait(POLL_INTERVAL)? && self.drain(buffer)?) {
+            return Ok(false);
+        }
+        while !self.stop.load(Ordering::Relaxed) && self.wait(self.debounce)? {
+            self.drain(buffer)?;
+        }
+        Ok(true)
+    }
+
+    /// Wait up to `timeout` for events, returning whether there are any.
+    fn wait(&self, timeout: Duration) -> io::Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.inotify.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+
+        // Safe, since `fd` is a single valid `pollfd` for the whole call.
+        match unsafe { libc::poll(std::ptr::addr_of_mut!(fd), 1, timeout) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    Ok(false)
+                } else {
+                    Err(err)
+                }
+            }
+            ready => Ok(ready > 0),
+        }
+    }
+
+    /// Read every pending event, returning whether any were about the
+    /// database.
+    fn drain(&mut self, buffer: &mut [u8]) -> io::Result<bool> {
+        let mut relevant = false;
+        loop {
+            let mut empty = true;
+            for event in self.inotify.read_events(buffer)? {
+                empty = false;
+                relevant |= event
+                    .name
+                    .is_some_and(|name| self.names.iter().any(|n| n == name));
+            }
+            if empty {
+                return Ok(relevant);
+            }
+        }
+    }
+}
+
+/// The file names of the database at `db_path` and its write-ahead log.
+fn watched_names(db_path: &Path) -> Vec<OsString> {
+    let name = db_path.file_name().unwrap_or_default().to_os_string();
+    let mut wal = name.clone();
+    wal.push("-wal");
+    vec![name, wal]
+}
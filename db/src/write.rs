@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::types::{ToSql, Value};
+use rusqlite::{Connection, OpenFlags};
+
+use super::{open_with_flags, table_columns, Album, Error, Item};
+
+/// How long a write waits for another connection, e.g. `beet` itself, to
+/// release its lock on the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The album fields that beets copies to each of an album's items when they
+/// change, from its `Album.item_keys`. Items keep their own `added`.
+const ITEM_KEYS: &[&str] = &[
+    "albumartist",
+    "albumartist_sort",
+    "albumartist_credit",
+    "albumartists",
+    "albumartists_sort",
+    "albumartists_credit",
+    "album",
+    "genre",
+    "genres",
+    "style",
+    "discogs_albumid",
+    "discogs_artistid",
+    "discogs_labelid",
+    "year",
+    "month",
+    "day",
+    "disctotal",
+    "comp",
+    "mb_albumid",
+    "mb_albumartistid",
+    "mb_albumartistids",
+    "albumtype",
+    "albumtypes",
+    "label",
+    "barcode",
+    "mb_releasegroupid",
+    "release_group_title",
+    "asin",
+    "catalognum",
+    "script",
+    "language",
+    "country",
+    "albumstatus",
+    "albumdisambig",
+    "releasegroupdisambig",
+    "rg_album_gain",
+    "rg_album_peak",
+    "r128_album_gain",
+    "original_year",
+    "original_month",
+    "original_day",
+];
+
+/// A set of changes to apply to a single album or item.
+///
+/// Field names are only checked against the table when the changes are
+/// applied, so the same `Changes` can be used for either kind of entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changes {
+    fields: BTreeMap<String, Value>,
+    flex: BTreeMap<String, Option<String>>,
+}
+
+impl Changes {
+    /// An empty set of changes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the column `field` to `value`.
+    #[must_use]
+    pub fn set<V: Into<Value>>(mut self, field: &str, value: V) -> Self {
+        self.fields.insert(field.to_string(), value.into());
+        self
+    }
+
+    /// Set the flexible attribute `key` to `value`, adding it if need be.
+    #[must_use]
+    pub fn set_flex<V: Into<String>>(mut self, key: &str, value: V) -> Self {
+        self.flex.insert(key.to_string(), Some(value.into()));
+        self
+    }
+
+    /// Remove the flexible attribute `key`, if it is present.
+    #[must_use]
+    pub fn remove_flex(mut self, key: &str) -> Self {
+        self.flex.insert(key.to_string(), None);
+        self
+    }
+
+    /// Whether there are no changes to apply.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.flex.is_empty()
+    }
+
+    /// Write these changes to the entry `id` of `table`, and its flexible
+    /// attributes in `attr_table`.
+    ///
+    /// Nothing is written unless every field name is a column of the table
+    /// as the database has it.
+    pub(crate) fn apply(
+        &self,
+        c: &Connection,
+        table: &'static str,
+        attr_table: &'static str,
+        id: u32,
+    ) -> Result<(), Error> {
+        let columns = table_columns(c, table)?;
+        for field in self.fields.keys() {
+            if field == "id" {
+                return Err(Error::ReadOnlyField { table, field: "id" });
+            }
+            if !columns.contains(field) {
+                return Err(Error::UnknownField {
+                    table,
+                    field: field.clone(),
+                });
+            }
+        }
+        if let Some(field) = self.flex.keys().find(|k| columns.contains(k)) {
+            return Err(Error::NotFlexible {
+                table,
+                field: field.clone(),
+            });
+        }
+
+        let exists = c
+            .prepare(&format!("SELECT 1 FROM {table} WHERE id = ?"))?
+            .exists(&[&id])?;
+        if !exists {
+            return Err(Error::NoSuchEntry { table, id });
+        }
+
+        update_columns(c, table, "id", id, self.fields.iter())?;
+
+        for (key, value) in &self.flex {
+            c.execute(
+                &format!("DELETE FROM {attr_table} WHERE entity_id = ? AND key = ?"),
+                &[&id as &dyn ToSql, key],
+            )?;
+            if let Some(value) = value {
+                c.execute(
+                    &format!("INSERT INTO {attr_table} (entity_id, key, value) VALUES (?, ?, ?)"),
+                    &[&id as &dyn ToSql, key, value],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy the fields that beets keeps on items too, such as `year`, from
+    /// the album `id` to its items, as beets does when an album is changed.
+    fn inherit(&self, c: &Connection, id: u32) -> Result<(), Error> {
+        let columns = table_columns(c, "items")?;
+        let fields = self
+            .fields
+            .iter()
+            .filter(|(field, _)| ITEM_KEYS.contains(&field.as_str()) && columns.contains(field));
+        update_columns(c, "items", "album_id", id, fields)
+    }
+}
+
+/// Set `fields` in the rows of `table` whose `key` is `id`, if there are any
+/// fields to set.
+fn update_columns<'a>(
+    c: &Connection,
+    table: &str,
+    key: &str,
+    id: u32,
+    fields: impl Iterator<Item = (&'a String, &'a Value)>,
+) -> Result<(), Error> {
+    let (columns, mut params): (Vec<_>, Vec<_>) = fields
+        .map(|(field, value)| (format!("\"{field}\" = ?"), value as &dyn ToSql))
+        .unzip();
+    if columns.is_empty() {
+        return Ok(());
+    }
+    params.push(&id);
+    c.execute(
+        &format!("UPDATE {table} SET {} WHERE {key} = ?", columns.join(", ")),
+        &params,
+    )?;
+    Ok(())
+}
+
+/// A connection to a beets database that can change its albums and items.
+///
+/// Reading never needs one of these: [`open`](fn.open.html) only ever opens
+/// the database read-only.
+pub struct Writer {
+    conn: Connection,
+}
+
+impl Writer {
+    /// Open the beets database at `db_path` for reading and writing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there is no file at `db_path`, if it is not a beets database,
+    /// or if `SQLite` cannot open it for writing.
+    pub fn open<P: AsRef<Path>>(db_path: P) -> Result<Self, Error> {
+        let conn = open_with_flags(db_path.as_ref(), OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(Self { conn })
+    }
+
+    /// The underlying connection, e.g. to read entries with.
+    #[must_use]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Apply `changes` to the item `id` and return it as it now stands.
+    ///
+    /// The changes are made in a single transaction, which is rolled back if
+    /// any of them fail.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there is no such item, if `changes` names a field that items
+    /// do not have (or sets a flexible attribute with the name of one), or
+    /// if a new value cannot be read back as the type of its field.
+    pub fn update_item(&mut self, id: u32, changes: &Changes) -> Result<Item, Error> {
+        let tx = self.conn.transaction()?;
+        let item = Item::update(&tx, id, changes)?;
+        tx.commit()?;
+        Ok(item)
+    }
+
+    /// Apply `changes` to the album `id` and return it as it now stands.
+    ///
+    /// Fields that beets keeps on items too, such as `year` or `genre`, are
+    /// changed in each of the album's items as well, as `beet modify -a`
+    /// does. `added` and flexible attributes are only changed on the album.
+    ///
+    /// # Errors
+    ///
+    /// See [`update_item`](#method.update_item).
+    pub fn update_album(&mut self, id: u32, changes: &Changes) -> Result<Album, Error> {
+        let tx = self.conn.transaction()?;
+        let album = Album::update(&tx, id, changes)?;
+        changes.inherit(&tx, id)?;
+        tx.commit()?;
+        Ok(album)
+    }
+}
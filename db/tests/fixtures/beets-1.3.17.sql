@@ -0,0 +1,137 @@
+-- Schema and a handful of rows as written by beets 1.3.17, which predates
+-- the lyricist, composer_sort, arranger, mb_releasetrackid and r128_* fields.
+-- Column defaults are only here to keep the inserts short.
+
+CREATE TABLE albums (
+    id INTEGER PRIMARY KEY,
+    artpath BLOB,
+    added REAL NOT NULL DEFAULT 0,
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE items (
+    id INTEGER PRIMARY KEY,
+    path BLOB NOT NULL DEFAULT x'',
+    album_id INTEGER,
+    title TEXT NOT NULL DEFAULT '',
+    artist TEXT NOT NULL DEFAULT '',
+    artist_sort TEXT NOT NULL DEFAULT '',
+    artist_credit TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    composer TEXT NOT NULL DEFAULT '',
+    grouping TEXT NOT NULL DEFAULT '',
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    track INTEGER NOT NULL DEFAULT 0,
+    tracktotal INTEGER NOT NULL DEFAULT 0,
+    disc INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    lyrics TEXT NOT NULL DEFAULT '',
+    comments TEXT NOT NULL DEFAULT '',
+    bpm INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_trackid TEXT NOT NULL DEFAULT '',
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_artistid TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    acoustid_fingerprint TEXT NOT NULL DEFAULT '',
+    acoustid_id TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    media TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    disctitle TEXT NOT NULL DEFAULT '',
+    encoder TEXT NOT NULL DEFAULT '',
+    rg_track_gain REAL,
+    rg_track_peak REAL,
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0,
+    initial_key TEXT,
+    length REAL NOT NULL DEFAULT 0,
+    bitrate INTEGER NOT NULL DEFAULT 0,
+    format TEXT NOT NULL DEFAULT '',
+    samplerate INTEGER NOT NULL DEFAULT 0,
+    bitdepth INTEGER NOT NULL DEFAULT 0,
+    channels INTEGER NOT NULL DEFAULT 0,
+    mtime REAL NOT NULL DEFAULT 0,
+    added REAL NOT NULL DEFAULT 0
+);
+
+CREATE TABLE album_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX album_attributes_by_entity ON album_attributes (entity_id);
+
+CREATE TABLE item_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX item_attributes_by_entity ON item_attributes (entity_id);
+
+INSERT INTO albums (id, artpath, added, albumartist, album, genre, year, month, day, disctotal, label)
+VALUES
+    (1, CAST('/music/Portishead/Dummy/cover.jpg' AS BLOB), 1420070400.0,
+     'Portishead', 'Dummy', 'Trip Hop', 1994, 8, 22, 1, 'Go! Beat');
+
+INSERT INTO items (id, path, album_id, title, artist, album, albumartist, genre, composer, year,
+                   month, day, track, tracktotal, disc, disctotal, length, bitrate, format,
+                   samplerate, bitdepth, channels, mtime, added)
+VALUES
+    (1, CAST('/music/Portishead/Dummy/01 Mysterons.mp3' AS BLOB), 1,
+     'Mysterons', 'Portishead', 'Dummy', 'Portishead', 'Trip Hop', 'Geoff Barrow', 1994,
+     8, 22, 1, 11, 1, 1, 302.3, 320000, 'MP3', 44100, 0, 2, 1420070400.0, 1420070400.0),
+    (2, CAST('/music/Portishead/Dummy/02 Sour Times.mp3' AS BLOB), 1,
+     'Sour Times', 'Portishead', 'Dummy', 'Portishead', 'Trip Hop', 'Geoff Barrow', 1994,
+     8, 22, 2, 11, 1, 1, 252.1, 320000, 'MP3', 44100, 0, 2, 1420070400.0, 1420070400.0);
+
+INSERT INTO item_attributes (entity_id, key, value)
+VALUES
+    (2, 'rating', '5');
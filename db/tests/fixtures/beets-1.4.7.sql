@@ -0,0 +1,158 @@
+-- Schema and a handful of rows as written by beets 1.4.7.
+-- Column defaults are only here to keep the inserts short.
+
+CREATE TABLE albums (
+    id INTEGER PRIMARY KEY,
+    artpath BLOB,
+    added REAL NOT NULL DEFAULT 0,
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    r128_album_gain INTEGER NOT NULL DEFAULT 0,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE items (
+    id INTEGER PRIMARY KEY,
+    path BLOB NOT NULL DEFAULT x'',
+    album_id INTEGER,
+    title TEXT NOT NULL DEFAULT '',
+    artist TEXT NOT NULL DEFAULT '',
+    artist_sort TEXT NOT NULL DEFAULT '',
+    artist_credit TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    lyricist TEXT NOT NULL DEFAULT '',
+    composer TEXT NOT NULL DEFAULT '',
+    composer_sort TEXT NOT NULL DEFAULT '',
+    arranger TEXT NOT NULL DEFAULT '',
+    grouping TEXT NOT NULL DEFAULT '',
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    track INTEGER NOT NULL DEFAULT 0,
+    tracktotal INTEGER NOT NULL DEFAULT 0,
+    disc INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    lyrics TEXT NOT NULL DEFAULT '',
+    comments TEXT NOT NULL DEFAULT '',
+    bpm INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_trackid TEXT NOT NULL DEFAULT '',
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_artistid TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    mb_releasetrackid TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    acoustid_fingerprint TEXT NOT NULL DEFAULT '',
+    acoustid_id TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    media TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    disctitle TEXT NOT NULL DEFAULT '',
+    encoder TEXT NOT NULL DEFAULT '',
+    rg_track_gain REAL,
+    rg_track_peak REAL,
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    r128_track_gain INTEGER NOT NULL DEFAULT 0,
+    r128_album_gain INTEGER NOT NULL DEFAULT 0,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0,
+    initial_key TEXT,
+    length REAL NOT NULL DEFAULT 0,
+    bitrate INTEGER NOT NULL DEFAULT 0,
+    format TEXT NOT NULL DEFAULT '',
+    samplerate INTEGER NOT NULL DEFAULT 0,
+    bitdepth INTEGER NOT NULL DEFAULT 0,
+    channels INTEGER NOT NULL DEFAULT 0,
+    mtime REAL NOT NULL DEFAULT 0,
+    added REAL NOT NULL DEFAULT 0
+);
+
+CREATE TABLE album_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX album_attributes_by_entity ON album_attributes (entity_id);
+
+CREATE TABLE item_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX item_attributes_by_entity ON item_attributes (entity_id);
+
+INSERT INTO albums (id, artpath, added, albumartist, album, genre, year, month, day, disctotal, label, country)
+VALUES
+    (1, CAST('/music/Boards of Canada/Geogaddi/cover.jpg' AS BLOB), 1546300800.0,
+     'Boards of Canada', 'Geogaddi', 'Electronic', 2002, 2, 18, 1, 'Warp Records', 'GB'),
+    (2, NULL, 1548979200.0,
+     'The Beatles', 'Abbey Road', 'Rock', 1969, 9, 26, 1, 'Apple Records', 'GB');
+
+INSERT INTO items (id, path, album_id, title, artist, album, albumartist, genre, year, month, day,
+                   track, tracktotal, disc, disctotal, length, bitrate, format, samplerate, bitdepth,
+                   channels, mtime, added)
+VALUES
+    (1, CAST('/music/Boards of Canada/Geogaddi/01 Ready Lets Go.flac' AS BLOB), 1,
+     'Ready Lets Go', 'Boards of Canada', 'Geogaddi', 'Boards of Canada', 'Electronic', 2002, 2, 18,
+     1, 23, 1, 1, 59.8, 900000, 'FLAC', 44100, 16, 2, 1546300800.0, 1546300800.0),
+    (2, CAST('/music/Boards of Canada/Geogaddi/02 Music Is Math.flac' AS BLOB), 1,
+     'Music Is Math', 'Boards of Canada', 'Geogaddi', 'Boards of Canada', 'Electronic', 2002, 2, 18,
+     2, 23, 1, 1, 321.5, 950000, 'FLAC', 44100, 16, 2, 1546300800.0, 1546300800.0),
+    (3, CAST('/music/The Beatles/Abbey Road/01 Come Together.mp3' AS BLOB), 2,
+     'Come Together', 'The Beatles', 'Abbey Road', 'The Beatles', 'Rock', 1969, 9, 26,
+     1, 17, 1, 1, 259.9, 320000, 'MP3', 44100, 0, 2, 1548979200.0, 1548979200.0),
+    (4, CAST('/music/Non-Album/Aphex Twin/Windowlicker.mp3' AS BLOB), NULL,
+     'Windowlicker', 'Aphex Twin', '', '', 'Electronic', 1999, 3, 22,
+     0, 0, 0, 0, 366.0, 256000, 'MP3', 44100, 0, 2, 1551398400.0, 1551398400.0);
+
+INSERT INTO album_attributes (entity_id, key, value)
+VALUES
+    (1, 'mood', 'hazy'),
+    (2, 'lastgenre_source', 'album');
+
+INSERT INTO item_attributes (entity_id, key, value)
+VALUES
+    (1, 'rating', '4'),
+    (1, 'play_count', '12'),
+    (3, 'rating', '5');
@@ -0,0 +1,187 @@
+-- Schema and a handful of rows as written by beets 2.0.0, which adds
+-- multi-valued artist fields, work/style/discogs fields and reorders columns.
+-- Column defaults are only here to keep the inserts short.
+
+CREATE TABLE albums (
+    id INTEGER PRIMARY KEY,
+    artpath BLOB,
+    added REAL NOT NULL DEFAULT 0,
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartists TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartists_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    albumartists_credit TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    style TEXT NOT NULL DEFAULT '',
+    discogs_albumid INTEGER NOT NULL DEFAULT 0,
+    discogs_artistid INTEGER NOT NULL DEFAULT 0,
+    discogs_labelid INTEGER NOT NULL DEFAULT 0,
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    mb_albumartistids TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    albumtypes TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    barcode TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    release_group_title TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    releasegroupdisambig TEXT NOT NULL DEFAULT '',
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    r128_album_gain INTEGER,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE items (
+    id INTEGER PRIMARY KEY,
+    path BLOB NOT NULL DEFAULT x'',
+    album_id INTEGER,
+    title TEXT NOT NULL DEFAULT '',
+    artist TEXT NOT NULL DEFAULT '',
+    artists TEXT NOT NULL DEFAULT '',
+    artist_sort TEXT NOT NULL DEFAULT '',
+    artists_sort TEXT NOT NULL DEFAULT '',
+    artist_credit TEXT NOT NULL DEFAULT '',
+    artists_credit TEXT NOT NULL DEFAULT '',
+    remixer TEXT NOT NULL DEFAULT '',
+    album TEXT NOT NULL DEFAULT '',
+    albumartist TEXT NOT NULL DEFAULT '',
+    albumartists TEXT NOT NULL DEFAULT '',
+    albumartist_sort TEXT NOT NULL DEFAULT '',
+    albumartists_sort TEXT NOT NULL DEFAULT '',
+    albumartist_credit TEXT NOT NULL DEFAULT '',
+    albumartists_credit TEXT NOT NULL DEFAULT '',
+    genre TEXT NOT NULL DEFAULT '',
+    style TEXT NOT NULL DEFAULT '',
+    discogs_albumid INTEGER NOT NULL DEFAULT 0,
+    discogs_artistid INTEGER NOT NULL DEFAULT 0,
+    discogs_labelid INTEGER NOT NULL DEFAULT 0,
+    lyricist TEXT NOT NULL DEFAULT '',
+    composer TEXT NOT NULL DEFAULT '',
+    composer_sort TEXT NOT NULL DEFAULT '',
+    work TEXT NOT NULL DEFAULT '',
+    mb_workid TEXT NOT NULL DEFAULT '',
+    work_disambig TEXT NOT NULL DEFAULT '',
+    arranger TEXT NOT NULL DEFAULT '',
+    grouping TEXT NOT NULL DEFAULT '',
+    year INTEGER NOT NULL DEFAULT 0,
+    month INTEGER NOT NULL DEFAULT 0,
+    day INTEGER NOT NULL DEFAULT 0,
+    track INTEGER NOT NULL DEFAULT 0,
+    tracktotal INTEGER NOT NULL DEFAULT 0,
+    disc INTEGER NOT NULL DEFAULT 0,
+    disctotal INTEGER NOT NULL DEFAULT 0,
+    lyrics TEXT NOT NULL DEFAULT '',
+    comments TEXT NOT NULL DEFAULT '',
+    bpm INTEGER NOT NULL DEFAULT 0,
+    comp INTEGER NOT NULL DEFAULT 0,
+    mb_trackid TEXT NOT NULL DEFAULT '',
+    mb_albumid TEXT NOT NULL DEFAULT '',
+    mb_artistid TEXT NOT NULL DEFAULT '',
+    mb_artistids TEXT NOT NULL DEFAULT '',
+    mb_albumartistid TEXT NOT NULL DEFAULT '',
+    mb_albumartistids TEXT NOT NULL DEFAULT '',
+    mb_releasetrackid TEXT NOT NULL DEFAULT '',
+    trackdisambig TEXT NOT NULL DEFAULT '',
+    albumtype TEXT NOT NULL DEFAULT '',
+    albumtypes TEXT NOT NULL DEFAULT '',
+    label TEXT NOT NULL DEFAULT '',
+    barcode TEXT NOT NULL DEFAULT '',
+    acoustid_fingerprint TEXT NOT NULL DEFAULT '',
+    acoustid_id TEXT NOT NULL DEFAULT '',
+    mb_releasegroupid TEXT NOT NULL DEFAULT '',
+    release_group_title TEXT NOT NULL DEFAULT '',
+    asin TEXT NOT NULL DEFAULT '',
+    isrc TEXT NOT NULL DEFAULT '',
+    catalognum TEXT NOT NULL DEFAULT '',
+    script TEXT NOT NULL DEFAULT '',
+    language TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    albumstatus TEXT NOT NULL DEFAULT '',
+    media TEXT NOT NULL DEFAULT '',
+    albumdisambig TEXT NOT NULL DEFAULT '',
+    releasegroupdisambig TEXT NOT NULL DEFAULT '',
+    disctitle TEXT NOT NULL DEFAULT '',
+    encoder TEXT NOT NULL DEFAULT '',
+    rg_track_gain REAL,
+    rg_track_peak REAL,
+    rg_album_gain REAL,
+    rg_album_peak REAL,
+    r128_track_gain INTEGER,
+    r128_album_gain INTEGER,
+    original_year INTEGER NOT NULL DEFAULT 0,
+    original_month INTEGER NOT NULL DEFAULT 0,
+    original_day INTEGER NOT NULL DEFAULT 0,
+    initial_key TEXT,
+    length REAL NOT NULL DEFAULT 0,
+    bitrate INTEGER NOT NULL DEFAULT 0,
+    bitrate_mode TEXT NOT NULL DEFAULT '',
+    encoder_info TEXT NOT NULL DEFAULT '',
+    encoder_settings TEXT NOT NULL DEFAULT '',
+    format TEXT NOT NULL DEFAULT '',
+    samplerate INTEGER NOT NULL DEFAULT 0,
+    bitdepth INTEGER NOT NULL DEFAULT 0,
+    channels INTEGER NOT NULL DEFAULT 0,
+    mtime REAL NOT NULL DEFAULT 0,
+    added REAL NOT NULL DEFAULT 0
+);
+
+CREATE TABLE album_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX album_attributes_by_entity ON album_attributes (entity_id);
+
+CREATE TABLE item_attributes (
+    id INTEGER PRIMARY KEY,
+    entity_id INTEGER,
+    key TEXT,
+    value TEXT,
+    UNIQUE(entity_id, key) ON CONFLICT REPLACE
+);
+CREATE INDEX item_attributes_by_entity ON item_attributes (entity_id);
+
+INSERT INTO albums (id, artpath, added, albumartist, albumartists, album, genre, style, year,
+                    month, day, disctotal, albumtype, albumtypes, label, country)
+VALUES
+    (1, CAST('/music/Daft Punk/Random Access Memories/cover.jpg' AS BLOB), 1704067200.0,
+     'Daft Punk', 'Daft Punk', 'Random Access Memories', 'Electronic', 'Disco', 2013,
+     5, 17, 1, 'album', 'album', 'Columbia', 'US');
+
+INSERT INTO items (id, path, album_id, title, artist, artists, album, albumartist, albumartists,
+                   genre, style, year, month, day, track, tracktotal, disc, disctotal,
+                   r128_track_gain, length, bitrate, bitrate_mode, format, samplerate,
+                   bitdepth, channels, mtime, added)
+VALUES
+    (1, CAST('/music/Daft Punk/Random Access Memories/01 Give Life Back to Music.flac' AS BLOB), 1,
+     'Give Life Back to Music', 'Daft Punk', 'Daft Punk', 'Random Access Memories',
+     'Daft Punk', 'Daft Punk', 'Electronic', 'Disco', 2013, 5, 17, 1, 13, 1, 1,
+     NULL, 274.5, 1000000, '', 'FLAC', 44100, 16, 2, 1704067200.0, 1704067200.0),
+    (2, CAST('/music/Daft Punk/Random Access Memories/08 Get Lucky.flac' AS BLOB), 1,
+     'Get Lucky', 'Daft Punk feat. Pharrell Williams & Nile Rodgers',
+     'Daft Punk\␀Pharrell Williams\␀Nile Rodgers', 'Random Access Memories',
+     'Daft Punk', 'Daft Punk', 'Electronic', 'Disco', 2013, 5, 17, 8, 13, 1, 1,
+     1280, 369.6, 1000000, '', 'FLAC', 44100, 16, 2, 1704067200.0, 1704067200.0);
+
+INSERT INTO item_attributes (entity_id, key, value)
+VALUES
+    (2, 'rating', '5');
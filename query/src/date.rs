@@ -0,0 +1,231 @@
+//! Dates in queries, such as `added:2019-01..2019-06` or `added:-1w..`.
+//!
+//! As in beets, dates are in local time: that of the clock's time zone, which
+//! is the system's unless the clock says otherwise.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use beet_db::local_time;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+/// Where relative dates like `-1w` are counted from, and the time zone that
+/// dates like `2019-06` are in.
+pub trait Clock {
+    /// The current time.
+    fn now(&self) -> SystemTime;
+
+    /// How many seconds local time is ahead of UTC, at the local time
+    /// `local` (in seconds since the Unix epoch, as if local time were UTC).
+    ///
+    /// Defaults to the system's time zone, or UTC where it cannot be read.
+    fn utc_offset(&self, local: i64) -> i64 {
+        local_offset(local)
+    }
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that is stopped at this time, in the system's time zone.
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// A clock that is stopped at `now`, in a time zone that is always `offset`
+/// seconds ahead of UTC.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedClock {
+    pub now: SystemTime,
+    pub offset: i64,
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.now
+    }
+
+    fn utc_offset(&self, _: i64) -> i64 {
+        self.offset
+    }
+}
+
+/// The offset of the system's time zone at the local time `local`.
+fn local_offset(local: i64) -> i64 {
+    // The offset at `local` read as UTC is off by a transition to or from
+    // summer time in between, so look again where that puts it.
+    let offset_at = local_time::utc_offset;
+    offset_at(local - offset_at(local))
+}
+
+/// A span of time, in seconds since the Unix epoch, from `start` up to but
+/// not including `end`. Either end may be open.
+#[derive(Debug, PartialEq)]
+pub struct DateRange {
+    start: Option<i64>,
+    end: Option<i64>,
+}
+
+impl DateRange {
+    pub fn start(&self) -> Option<i64> {
+        self.start
+    }
+
+    pub fn end(&self) -> Option<i64> {
+        self.end
+    }
+
+    /// Parse a date, such as `2019-06`, or a range of them, such as
+    /// `2019..2019-06-30T18` or `-3d..`, or return `None` if `s` is neither.
+    pub(crate) fn parse(s: &str, clock: &dyn Clock) -> Option<Self> {
+        if s.is_empty() {
+            return None;
+        }
+        let (start, end) = match s.find("..") {
+            Some(idx) => (&s[..idx], &s[idx + 2..]),
+            None => (s, s),
+        };
+        let period = |s: &str| {
+            if s.is_empty() {
+                Some(None)
+            } else {
+                Period::parse(s, clock).map(Some)
+            }
+        };
+
+        Some(Self {
+            start: period(start)?.map(|period| period.start),
+            end: period(end)?.map(|period| period.end),
+        })
+    }
+
+    pub(crate) fn contains(&self, seconds: f64) -> bool {
+        self.start.is_none_or(|start| seconds >= start as f64)
+            && self.end.is_none_or(|end| seconds < end as f64)
+    }
+}
+
+/// The span of time that a date stands for, e.g. all of 2019 for `2019`.
+struct Period {
+    start: i64,
+    end: i64,
+}
+
+impl Period {
+    fn parse(s: &str, clock: &dyn Clock) -> Option<Self> {
+        Self::relative(s, clock).or_else(|| {
+            let local = Self::absolute(s)?;
+            let utc = |local: i64| local - clock.utc_offset(local);
+            Some(Self {
+                start: utc(local.start),
+                end: utc(local.end),
+            })
+        })
+    }
+
+    /// A number of days, weeks, months (of 30 days) or years (of 365 days)
+    /// from now, such as `-3d` or `1w`, to the second.
+    fn relative(s: &str, clock: &dyn Clock) -> Option<Self> {
+        let (sign, s) = match s.as_bytes().first()? {
+            b'-' => (-1, &s[1..]),
+            b'+' => (1, &s[1..]),
+            _ => (1, s),
+        };
+        let days = match s.chars().last()? {
+            'd' => 1,
+            'w' => 7,
+            'm' => 30,
+            'y' => 365,
+            _ => return None,
+        };
+        let quantity = digits(&s[..s.len() - 1], 1, usize::MAX)?;
+
+        let now = match clock.now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        };
+        let start = now.checked_add(sign * quantity.checked_mul(days * DAY)?)?;
+        Some(Self {
+            start,
+            end: start + 1,
+        })
+    }
+
+    /// `2019`, `2019-06`, `2019-06-30` or `2019-06-30T18`, as if local time
+    /// were UTC.
+    fn absolute(s: &str) -> Option<Self> {
+        let (date, hour) = match s.find('T') {
+            Some(idx) => (&s[..idx], Some(digits(&s[idx + 1..], 1, 2)?)),
+            None => (s, None),
+        };
+        let parts = date.split('-').collect::<Vec<_>>();
+        if parts.len() > 3 || (hour.is_some() && parts.len() < 3) {
+            return None;
+        }
+        let year = digits(parts[0], 4, 4)?;
+        let month = match parts.get(1) {
+            Some(month) => Some(digits(month, 1, 2)?),
+            None => None,
+        };
+        let day = match parts.get(2) {
+            Some(day) => Some(digits(day, 1, 2)?),
+            None => None,
+        };
+
+        let first_of_next = |year, month| {
+            if month == 12 {
+                days_from_civil(year + 1, 1, 1)
+            } else {
+                days_from_civil(year, month + 1, 1)
+            }
+        };
+        let valid = month.is_none_or(|month| (1..=12).contains(&month))
+            && day.is_none_or(|day| {
+                let month = month.unwrap_or(1);
+                day >= 1 && day <= first_of_next(year, month) - days_from_civil(year, month, 1)
+            })
+            && hour.is_none_or(|hour| hour < 24);
+        if !valid {
+            return None;
+        }
+
+        let start = days_from_civil(year, month.unwrap_or(1), day.unwrap_or(1)) * DAY
+            + hour.unwrap_or(0) * HOUR;
+        let end = match (month, day, hour) {
+            (None, ..) => days_from_civil(year + 1, 1, 1) * DAY,
+            (Some(month), None, _) => first_of_next(year, month) * DAY,
+            (_, Some(_), None) => start + DAY,
+            (_, _, Some(_)) => start + HOUR,
+        };
+        Some(Self { start, end })
+    }
+}
+
+/// Parse between `min` and `max` ASCII digits.
+fn digits(s: &str, min: usize, max: usize) -> Option<i64> {
+    if s.len() < min || s.len() > max || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// The number of days from the Unix epoch to a date, from Howard Hinnant's
+/// `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
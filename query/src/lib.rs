@@ -1,11 +1,58 @@
+use std::fmt;
+use std::iter::Peekable;
 use std::str::FromStr;
 
-use beet_db::{Album, Item};
+use beet_db::{Album, AlbumRef, Item, Value};
+use regex::Regex;
 
+mod date;
+mod path;
 mod tests;
 
+pub use date::{Clock, DateRange, FixedClock, SystemClock};
+pub use path::PathPattern;
+
+/// The fields that hold dates, in seconds since the Unix epoch.
+const DATE_FIELDS: &[&str] = &["added", "mtime"];
+
+/// Everything that can make a query invalid.
 #[derive(Debug)]
-pub struct Error;
+pub enum Error {
+    /// A regular expression query has a pattern that does not compile.
+    Regex {
+        pattern: String,
+        source: regex::Error,
+    },
+    /// A date query, such as `added:2019-06`, has something other than a
+    /// date or a range of them.
+    Date(String),
+    /// A `(` without a matching `)`, or the other way around.
+    UnbalancedParentheses,
+    /// A comma with nothing to match on one side of it, as in `artist:a ,`.
+    EmptyAlternative,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Regex { pattern, source } => {
+                write!(f, "Invalid regular expression `{}`: {}", pattern, source)
+            }
+            Error::Date(text) => write!(f, "Invalid date or range of dates `{}`", text),
+            Error::UnbalancedParentheses => write!(f, "Unbalanced parentheses"),
+            Error::EmptyAlternative => write!(f, "Nothing to match on one side of a comma"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Regex { source, .. } => Some(source),
+            Error::Date(_) | Error::UnbalancedParentheses | Error::EmptyAlternative => None,
+        }
+    }
+}
 
 #[derive(Debug, Default, PartialEq)]
 pub struct Query {
@@ -14,8 +61,37 @@ pub struct Query {
 }
 
 impl Query {
+    /// Parse a query, counting relative dates like `-1w` back from the time
+    /// that `clock` gives rather than the current time.
+    pub fn parse_with_clock(s: &str, clock: &dyn Clock) -> Result<Self, Error> {
+        let mut sort = Vec::new();
+        let mut tokens = tokenize(s).into_iter().peekable();
+        let keys = KeyGroup::parse(&mut tokens, &mut sort, clock)?;
+        if tokens.next().is_some() {
+            return Err(Error::UnbalancedParentheses);
+        }
+
+        Ok(Self { keys, sort })
+    }
+
+    /// The conditions of the query, which all have to match when there are
+    /// no commas or parentheses.
+    pub fn keys(&self) -> &KeyGroup {
+        &self.keys
+    }
+
+    /// Whether the query matches `album`. A `path:` query matches its
+    /// `artpath`; see [`match_album_ref`](#method.match_album_ref) to match
+    /// the directories of its items too.
     pub fn match_album(&self, album: &Album) -> bool {
-        self.keys.match_album(album)
+        self.keys.match_album(album, &[])
+    }
+
+    /// Whether the query matches `album`, where a `path:` query matches
+    /// either its `artpath` or the directory of one of its items.
+    pub fn match_album_ref(&self, album: AlbumRef<'_>) -> bool {
+        let items = album.items().map(|item| item.as_item()).collect::<Vec<_>>();
+        self.keys.match_album(&album, &items)
     }
 
     pub fn match_item(&self, item: &Item) -> bool {
@@ -27,23 +103,7 @@ impl FromStr for Query {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut new = Self::default();
-
-        for token in s.split(' ') {
-            if token.ends_with('+') || token.ends_with('-') {
-                match token.parse::<Sort>() {
-                    Ok(sort) => new.sort.push(sort),
-                    Err(err) => return Err(err),
-                }
-            } else {
-                match token.parse::<Keyword>() {
-                    Ok(key) => new.keys.keys.push(key),
-                    Err(err) => return Err(err),
-                }
-            }
-        }
-
-        Ok(new)
+        Self::parse_with_clock(s, &SystemClock)
     }
 }
 
@@ -64,16 +124,134 @@ impl FromStr for Sort {
     }
 }
 
+/// The pieces of a query string: `(`, `)`, `,` and everything else.
 #[derive(Debug, PartialEq)]
-#[allow(dead_code)]
-struct KeyGroup {
-    keys: Vec<Keyword>,
+enum Token<'a> {
+    Open,
+    Close,
+    Comma,
+    Word(&'a str),
+}
+
+/// Split a query on spaces, then split parentheses and commas off the words.
+///
+/// A `)` is only split off while the word has more of them than `(`, so
+/// that a regular expression like `title::(a|b)` stays whole. A comma
+/// escaped as `\\,` is not split off, and is kept as a literal comma.
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for mut word in s.split(' ') {
+        while let Some(rest) = word.strip_prefix('(') {
+            tokens.push(Token::Open);
+            word = rest;
+        }
+
+        let mut after = Vec::new();
+        loop {
+            if let Some(rest) = word.strip_suffix(',').filter(|rest| !rest.ends_with('\\')) {
+                after.push(Token::Comma);
+                word = rest;
+            } else if word.ends_with(')') && word.matches(')').count() > word.matches('(').count() {
+                after.push(Token::Close);
+                word = &word[..word.len() - 1];
+            } else {
+                break;
+            }
+        }
+
+        if !word.is_empty() {
+            tokens.push(Token::Word(word));
+        }
+        tokens.extend(after.into_iter().rev());
+    }
+    tokens
+}
+
+/// Conditions that either all or any have to match, as beets joins the
+/// keywords of a query with `and`, and the groups separated by commas with
+/// `or`.
+#[derive(Debug, PartialEq)]
+pub struct KeyGroup {
+    keys: Vec<Key>,
     all: bool,
 }
 
+/// A condition of a [`KeyGroup`](struct.KeyGroup.html).
+#[derive(Debug, PartialEq)]
+pub enum Key {
+    Keyword(Keyword),
+    /// A nested group, from a comma-separated part or from parentheses.
+    Group(KeyGroup),
+}
+
 impl KeyGroup {
-    fn match_album(&self, album: &Album) -> bool {
-        let f = |key: &Keyword| key.match_album(album);
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Whether all of the keys have to match, rather than any of them.
+    pub fn all(&self) -> bool {
+        self.all
+    }
+
+    /// Parse keywords up to a `)` or the end of `tokens`, with commas
+    /// separating alternatives. A single alternative is not wrapped in a
+    /// group of its own, and an empty one is an error rather than matching
+    /// everything.
+    fn parse<'a, I: Iterator<Item = Token<'a>>>(
+        tokens: &mut Peekable<I>,
+        sort: &mut Vec<Sort>,
+        clock: &dyn Clock,
+    ) -> Result<Self, Error> {
+        let mut alternatives = vec![Self::default()];
+        loop {
+            let key = match tokens.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Comma) => {
+                    tokens.next();
+                    alternatives.push(Self::default());
+                    continue;
+                }
+                Some(Token::Open) => {
+                    tokens.next();
+                    let group = Self::parse(tokens, sort, clock)?;
+                    if tokens.next() != Some(Token::Close) {
+                        return Err(Error::UnbalancedParentheses);
+                    }
+                    Key::Group(group)
+                }
+                Some(&Token::Word(word)) => {
+                    tokens.next();
+                    if word.ends_with('+') || word.ends_with('-') {
+                        sort.push(word.parse()?);
+                        continue;
+                    }
+                    Key::Keyword(Keyword::parse(&word.replace("\\,", ","), clock)?)
+                }
+            };
+            if let Some(group) = alternatives.last_mut() {
+                group.keys.push(key);
+            }
+        }
+
+        if alternatives.len() > 1 && alternatives.iter().any(|group| group.keys.is_empty()) {
+            return Err(Error::EmptyAlternative);
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(Self {
+                keys: alternatives.into_iter().map(Key::Group).collect(),
+                all: false,
+            })
+        }
+    }
+
+    fn match_album(&self, album: &Album, items: &[&Item]) -> bool {
+        let f = |key: &Key| match key {
+            Key::Keyword(keyword) => keyword.match_album(album, items),
+            Key::Group(group) => group.match_album(album, items),
+        };
 
         if self.all {
             self.keys.iter().all(f)
@@ -83,7 +261,10 @@ impl KeyGroup {
     }
 
     fn match_item(&self, item: &Item) -> bool {
-        let f = |key: &Keyword| key.match_item(item);
+        let f = |key: &Key| match key {
+            Key::Keyword(keyword) => keyword.match_item(item),
+            Key::Group(group) => group.match_item(item),
+        };
 
         if self.all {
             self.keys.iter().all(f)
@@ -102,8 +283,9 @@ impl Default for KeyGroup {
     }
 }
 
+/// A single condition, such as `artist:beatles` or `^year:1990..1999`.
 #[derive(Debug, Default, PartialEq)]
-struct Keyword {
+pub struct Keyword {
     text: String,
     field: Option<String>,
     key_type: Type,
@@ -111,13 +293,37 @@ struct Keyword {
 }
 
 impl Keyword {
-    fn match_album(&self, album: &Album) -> bool {
+    /// What to look for, without the field and negation.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The field to look in, or `None` for the default fields.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn key_type(&self) -> &Type {
+        &self.key_type
+    }
+
+    /// Whether the keyword has to not match, as with `^artist:beatles`.
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+
+    fn match_album(&self, album: &Album, items: &[&Item]) -> bool {
+        let value = self.field.as_deref().and_then(|field| album.get(field));
+        if let Some(matched) = self.match_value(value) {
+            return self.negated != matched;
+        }
+
         let year = format!("{}", album.year);
         let month = format!("{}", album.month);
         let day = format!("{}", album.day);
         let disctotal = format!("{}", album.disctotal);
 
-        let txt = match self.field.as_ref().map(String::as_str) {
+        let txt = match self.field.as_deref() {
             Some("album") => vec![&album.album, &album.albumdisambig],
             Some("albumartist") => vec![
                 &album.albumartist,
@@ -142,16 +348,29 @@ impl Keyword {
 
         self.negated
             != match self.key_type {
-                Type::Basic => {
+                Type::Basic | Type::NumRange(_) | Type::DateRange(_) => {
                     let lower = self.text.to_lowercase();
                     txt.iter().any(|s| s.to_lowercase().contains(&lower))
                 }
-                Type::Path => unimplemented!(),
-                // _ => unreachable!(),
+                Type::Path(ref pattern) => {
+                    album
+                        .artpath
+                        .as_ref()
+                        .is_some_and(|artpath| pattern.matches(artpath))
+                        || items
+                            .iter()
+                            .any(|item| item.path.parent().is_some_and(|dir| pattern.matches(dir)))
+                }
+                Type::Regex(ref regex) => txt.iter().any(|s| regex.0.is_match(s)),
             }
     }
 
     fn match_item(&self, item: &Item) -> bool {
+        let value = self.field.as_deref().and_then(|field| item.get(field));
+        if let Some(matched) = self.match_value(value) {
+            return self.negated != matched;
+        }
+
         let year = format!("{}", item.year);
         let month = format!("{}", item.month);
         let day = format!("{}", item.day);
@@ -161,7 +380,7 @@ impl Keyword {
         let disctotal = format!("{}", item.disctotal);
         let bitrate = format!("{}", item.bitrate);
 
-        let txt = match self.field.as_ref().map(String::as_str) {
+        let txt = match self.field.as_deref() {
             Some("title") => vec![&item.title],
             Some("album") => vec![&item.album],
             Some("artist") => vec![&item.artist, &item.artist_sort, &item.artist_credit],
@@ -202,20 +421,41 @@ impl Keyword {
 
         self.negated
             != match self.key_type {
-                Type::Basic => {
+                Type::Basic | Type::NumRange(_) | Type::DateRange(_) => {
                     let lower = self.text.to_lowercase();
                     txt.iter().any(|s| s.to_lowercase().contains(&lower))
                 }
-                Type::Path => unimplemented!(),
-                // _ => unreachable!(),
+                Type::Path(ref pattern) => pattern.matches(&item.path),
+                Type::Regex(ref regex) => txt.iter().any(|s| regex.0.is_match(s)),
             }
     }
-}
 
-impl FromStr for Keyword {
-    type Err = Error;
+    /// Match a regular expression against the value of any field or
+    /// flexible attribute, or compare the value of a numeric field to a
+    /// range of numbers or dates. Returns `None` if there is no such field,
+    /// or this is another kind of query, so that it is matched as text
+    /// instead.
+    fn match_value(&self, value: Option<Value>) -> Option<bool> {
+        let value = value?;
+        if let Type::Regex(ref regex) = self.key_type {
+            return Some(value.as_text().is_some_and(|text| regex.0.is_match(&text)));
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let contains = |n: f64| match self.key_type {
+            Type::NumRange(ref range) => Some(range.contains(n)),
+            Type::DateRange(ref range) => Some(range.contains(n)),
+            _ => None,
+        };
+        match value {
+            Value::Integer(n) => contains(n as f64),
+            Value::Real(n) => contains(n),
+            // An unset number, such as a missing ReplayGain value.
+            Value::Null => contains(0.0).map(|_| false),
+            _ => None,
+        }
+    }
+
+    fn parse(s: &str, clock: &dyn Clock) -> Result<Self, Error> {
         let mut new = Self::default();
         let mut curr_str = s.trim();
 
@@ -225,32 +465,117 @@ impl FromStr for Keyword {
         }
 
         if let Some(idx) = curr_str.find(':') {
-            match &curr_str[..idx] {
-                "path" => new.key_type = Type::Path,
-                // TODO: add regex support here
-                other => new.field = Some(other.to_string()),
-            }
+            let field = &curr_str[..idx];
             curr_str = &curr_str[idx + 1..];
+
+            if field.is_empty() || curr_str.starts_with(':') {
+                // `field::pattern`, or `:pattern` to search the default fields.
+                if !field.is_empty() {
+                    curr_str = &curr_str[1..];
+                    new.field = Some(field.to_string());
+                }
+                let regex = Regex::new(curr_str).map_err(|source| Error::Regex {
+                    pattern: curr_str.to_string(),
+                    source,
+                })?;
+                new.key_type = Type::Regex(Pattern(regex));
+            } else if field == "path" {
+                new.key_type = Type::Path(PathPattern::new(curr_str));
+            } else {
+                new.field = Some(field.to_string());
+            }
         }
 
-        // TODO: add num and date range support here
+        match new.field.as_deref() {
+            Some(field) if new.key_type == Type::Basic && DATE_FIELDS.contains(&field) => {
+                let range = DateRange::parse(curr_str, clock)
+                    .ok_or_else(|| Error::Date(curr_str.to_string()))?;
+                new.key_type = Type::DateRange(range);
+            }
+            Some(_) if new.key_type == Type::Basic => {
+                if let Some(range) = NumRange::parse(curr_str) {
+                    new.key_type = Type::NumRange(range);
+                }
+            }
+            _ => {}
+        }
         new.text = curr_str.to_string();
 
         Ok(new)
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Type {
+/// How a keyword matches.
+#[derive(Debug, Default, PartialEq)]
+pub enum Type {
+    /// A case-insensitive substring.
+    #[default]
     Basic,
-    Path,
-    // Regex,
-    // NumRange,
-    // DateRange,
+    /// `path:`, a file or the directory of one.
+    Path(PathPattern),
+    /// `field::pattern`, or `:pattern` for the default fields.
+    Regex(Pattern),
+    NumRange(NumRange),
+    DateRange(DateRange),
 }
 
-impl Default for Type {
-    fn default() -> Self {
-        Type::Basic
+/// A compiled regular expression, which is case sensitive unless it starts
+/// with `(?i)`, as in beets.
+#[derive(Debug)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+/// A range of numbers like `1990..1999`, `120..` or `..320000`, including
+/// both ends, or a single number like `1999`.
+#[derive(Debug, PartialEq)]
+pub struct NumRange {
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl NumRange {
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let (min, max) = match s.find("..") {
+            Some(idx) => (&s[..idx], &s[idx + 2..]),
+            None => (s, s),
+        };
+        let parse = |s: &str| {
+            if s.is_empty() {
+                Some(None)
+            } else {
+                s.parse().ok().filter(|n: &f64| n.is_finite()).map(Some)
+            }
+        };
+
+        if s.is_empty() {
+            return None;
+        }
+        Some(Self {
+            min: parse(min)?,
+            max: parse(max)?,
+        })
+    }
+
+    fn contains(&self, n: f64) -> bool {
+        self.min.is_none_or(|min| n >= min) && self.max.is_none_or(|max| n <= max)
     }
 }
@@ -0,0 +1,133 @@
+//! `path:` queries, such as `path:~/Music/Boards\ of\ Canada`.
+//!
+//! As in beets, the path is made absolute, and whether it matches case is
+//! found by probing the filesystem it is on.
+
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+/// A file, or a directory to match everything in, from a `path:` query.
+#[derive(Debug, PartialEq)]
+pub struct PathPattern {
+    path: PathBuf,
+    case_sensitive: bool,
+}
+
+impl PathPattern {
+    /// Expand `~` and make `query` absolute, without following links, then
+    /// probe whether it matches case.
+    pub(crate) fn new(query: &str) -> Self {
+        let path = normalize(&absolute(&expand_home(query)));
+        let case_sensitive = case_sensitive(&path);
+        Self {
+            path,
+            case_sensitive,
+        }
+    }
+
+    /// The absolute path that the query names.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the filesystem that the path is on tells paths that differ
+    /// only in case apart.
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Whether `path` is the file this names, or is in the directory it
+    /// names. Only whole components match, so `/music/a` does not match
+    /// `/music/abba`.
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        if self.case_sensitive {
+            path.starts_with(&self.path)
+        } else {
+            let path = path.to_string_lossy().to_lowercase();
+            Path::new(&path).starts_with(self.path.to_string_lossy().to_lowercase())
+        }
+    }
+}
+
+/// Replace a leading `~` with the home directory, if there is one.
+fn expand_home(query: &str) -> PathBuf {
+    let home = || env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+    let rest = match query.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(std::path::is_separator) => rest,
+        _ => return PathBuf::from(query),
+    };
+    match home() {
+        Some(mut path) => {
+            path.push(rest);
+            PathBuf::from(path)
+        }
+        None => PathBuf::from(query),
+    }
+}
+
+/// `path` relative to the working directory, if it is not absolute already
+/// and there is one.
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+}
+
+/// Remove `.` and resolve `..` without looking at the filesystem, as
+/// Python's `os.path.normpath` does.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if let Some(Component::Normal(_)) = normal.components().next_back() {
+                    normal.pop();
+                } else if !normal.has_root() {
+                    normal.push("..");
+                }
+            }
+            component => normal.push(component),
+        }
+    }
+    normal
+}
+
+/// Whether the filesystem at `path` is case sensitive, from whether its
+/// lower and upper case versions are the same file. A path that does not
+/// exist is taken to be case sensitive, except on Windows, as beets does.
+fn case_sensitive(path: &Path) -> bool {
+    if !path.exists() {
+        return !cfg!(windows);
+    }
+    let text = match path.to_str() {
+        Some(text) => text,
+        None => return true,
+    };
+    let (lower, upper) = (
+        PathBuf::from(text.to_lowercase()),
+        PathBuf::from(text.to_uppercase()),
+    );
+    if !(lower.exists() && upper.exists()) {
+        return true;
+    }
+    !same_file(&lower, &upper)
+}
+
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (a.metadata(), b.metadata()) {
+        (Ok(a), Ok(b)) => (a.dev(), a.ino()) == (b.dev(), b.ino()),
+        _ => false,
+    }
+}
+
+/// Without inodes to compare, both versions existing is taken to mean that
+/// they are the same.
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+    a.exists() && b.exists()
+}
@@ -1,5 +1,10 @@
 #![cfg(test)]
 
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
 use super::*;
 
 #[test]
@@ -34,3 +39,277 @@ fn sort_only() -> Result<(), Error> {
 
     Ok(())
 }
+
+const BEETS_1_4_7: &str = include_str!("../../db/tests/fixtures/beets-1.4.7.sql");
+
+/// 2019-03-04T00:00:00Z, three days after the last item was added, in UTC.
+fn utc() -> FixedClock {
+    FixedClock {
+        now: UNIX_EPOCH + Duration::from_secs(1_551_657_600),
+        offset: 0,
+    }
+}
+
+fn matching_items(query: &str) -> Result<Vec<u32>, Error> {
+    matching_items_at(query, &utc())
+}
+
+fn matching_items_at(query: &str, clock: &dyn Clock) -> Result<Vec<u32>, Error> {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(BEETS_1_4_7).unwrap();
+    let query = Query::parse_with_clock(query, clock)?;
+    Ok(Item::read_all(&conn)
+        .unwrap()
+        .iter()
+        .filter(|item| query.match_item(item))
+        .map(|item| item.id)
+        .collect())
+}
+
+#[test]
+fn regex() -> Result<(), Error> {
+    assert_eq!(matching_items("title::^M")?, [2]);
+    assert!(matching_items("title::^m")?.is_empty());
+    assert_eq!(matching_items("title::(?i)^m")?, [2]);
+    assert_eq!(matching_items(":Beatles$")?, [3]);
+    assert_eq!(matching_items("^artist::Canada")?, [3, 4]);
+    assert_eq!(matching_items("artist::Canada title::Go$")?, [1]);
+    assert_eq!(matching_items("year::^19")?, [3, 4]);
+    assert_eq!(matching_items("rating::^[45]$")?, [1, 3]);
+    assert_eq!(matching_items("samplerate::^44")?, [1, 2, 3, 4]);
+    assert_eq!(matching_items("path::Non-Album")?, [4]);
+
+    match matching_items("title::(") {
+        Err(Error::Regex { pattern, .. }) => assert_eq!(pattern, "("),
+        other => panic!("{:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn numeric_ranges() -> Result<(), Error> {
+    assert_eq!(matching_items("year:1990..1999")?, [4]);
+    assert_eq!(matching_items("year:1969")?, [3]);
+    assert!(matching_items("year:196")?.is_empty());
+    assert_eq!(matching_items("year:2000..")?, [1, 2]);
+    assert_eq!(matching_items("bitrate:..320000")?, [3, 4]);
+    assert_eq!(matching_items("length:300..366.0")?, [2, 4]);
+    assert_eq!(matching_items("^length:..300")?, [2, 4]);
+    assert_eq!(matching_items("year:1960.. genre:rock")?, [3]);
+    assert!(matching_items("rg_track_gain:..0")?.is_empty());
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(BEETS_1_4_7).unwrap();
+    let query = "year:..1999".parse::<Query>()?;
+    let albums = Album::read_all(&conn).unwrap();
+    assert!(!query.match_album(&albums[0]) && query.match_album(&albums[1]));
+    Ok(())
+}
+
+#[test]
+fn date_ranges() -> Result<(), Error> {
+    assert_eq!(matching_items("added:2019-01")?, [1, 2]);
+    assert_eq!(matching_items("added:2019-01..2019-02")?, [1, 2, 3]);
+    assert_eq!(matching_items("added:2019-02-01T00")?, [3]);
+    assert!(matching_items("added:2019-02-01T01")?.is_empty());
+    assert_eq!(matching_items("added:2019")?, [1, 2, 3, 4]);
+    assert!(matching_items("added:..2018")?.is_empty());
+    assert_eq!(matching_items("mtime:2019-03")?, [4]);
+
+    assert_eq!(matching_items("added:-1w..")?, [4]);
+    assert_eq!(matching_items("added:-5w..-1w")?, [3]);
+    assert_eq!(matching_items("added:-2m..")?, [3, 4]);
+    assert_eq!(matching_items("^added:..-1y")?, [1, 2, 3, 4]);
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(BEETS_1_4_7).unwrap();
+    let albums = Album::read_all(&conn).unwrap();
+    let query = Query::parse_with_clock("added:2019-02..", &utc())?;
+    assert!(!query.match_album(&albums[0]) && query.match_album(&albums[1]));
+    assert!(!Query::parse_with_clock("mtime:2019", &utc())?.match_album(&albums[0]));
+
+    for bad in &[
+        "added:2019-13",
+        "added:2019-02-29",
+        "added:yesterday",
+        "mtime:2019-01-01T24",
+    ] {
+        match bad.parse::<Query>() {
+            Err(Error::Date(text)) => assert_eq!(text, bad.split(':').nth(1).unwrap()),
+            other => panic!("{}: {:?}", bad, other),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn dates_in_local_time() -> Result<(), Error> {
+    // Item 3 was added at midnight UTC on 2019-02-01, when it was still
+    // 2019-01-31 five hours behind, and already 01:00 an hour ahead.
+    let behind = FixedClock {
+        offset: -5 * 60 * 60,
+        ..utc()
+    };
+    assert!(matching_items_at("added:2019-02-01", &behind)?.is_empty());
+    assert_eq!(matching_items_at("added:2019-01-31", &behind)?, [3]);
+    assert_eq!(matching_items_at("added:2019-01", &behind)?, [3]);
+    assert_eq!(matching_items_at("added:-1w..", &behind)?, [4]);
+
+    let ahead = FixedClock {
+        offset: 60 * 60,
+        ..utc()
+    };
+    assert_eq!(matching_items_at("added:2019-02-01T01", &ahead)?, [3]);
+    assert!(matching_items_at("added:2019-02-01T00", &ahead)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn paths() -> Result<(), Error> {
+    assert_eq!(matching_items("path:/music")?, [1, 2, 3, 4]);
+    assert_eq!(matching_items("path:/music/Non-Album/")?, [4]);
+    assert!(matching_items("path:/music/Non")?.is_empty());
+    assert_eq!(matching_items("^path:/music/Non-Album")?, [1, 2, 3]);
+    // There is no `/MUSIC/non-album` to probe, so as in beets it is taken
+    // to be case sensitive, except on Windows.
+    let ignoring_case = matching_items("path:/MUSIC/non-album")?;
+    assert_eq!(ignoring_case.is_empty(), !cfg!(windows));
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(BEETS_1_4_7).unwrap();
+    let mut albums = Album::read_all(&conn).unwrap();
+    let mut items = Item::read_all(&conn).unwrap();
+    albums[0].artpath = None;
+    albums[1].artpath = Some("/art/abbey-road.jpg".into());
+    items[0].path = "/music/boc/geogaddi/01.flac".into();
+    items[1].path = "/music/boc/geogaddi/02.flac".into();
+
+    let file = "path:/music/boc/geogaddi/01.flac".parse::<Query>()?;
+    let ids = items
+        .iter()
+        .filter(|item| file.match_item(item))
+        .map(|item| item.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [1]);
+
+    let library = beet_db::Library::new(albums, items);
+    let matching_albums = |query: &str| -> Result<Vec<u32>, Error> {
+        let query = query.parse::<Query>()?;
+        Ok(library
+            .albums()
+            .filter(|album| query.match_album_ref(*album))
+            .map(|album| album.id)
+            .collect())
+    };
+    assert_eq!(matching_albums("path:/music/boc")?, [1]);
+    assert_eq!(matching_albums("path:/art")?, [2]);
+    assert!(matching_albums("path:/music/boc/geogaddi/01.flac")?.is_empty());
+
+    let query = "path:/music/boc".parse::<Query>()?;
+    assert!(!query.match_album(&library.album(1).unwrap()));
+    Ok(())
+}
+
+#[test]
+fn paths_are_normalized() -> Result<(), Error> {
+    let matches = |query: &str, path: PathBuf| -> Result<bool, Error> {
+        let item = Item {
+            path,
+            ..Item::default()
+        };
+        Ok(query.parse::<Query>()?.match_item(&item))
+    };
+    let dir = env::current_dir().unwrap();
+    assert!(matches("path:music", dir.join("music/a.flac"))?);
+    assert!(matches("path:./music/../music/", dir.join("music/a.flac"))?);
+    assert!(!matches("path:music", PathBuf::from("/music/a.flac"))?);
+    assert!(matches(
+        "path:/music/boc/../aphex",
+        "/music/aphex/a.flac".into()
+    )?);
+    if let Some(home) = env::var_os("HOME") {
+        assert!(matches(
+            "path:~/Music",
+            Path::new(&home).join("Music/a.flac")
+        )?);
+    }
+    Ok(())
+}
+
+#[test]
+fn path_case_is_probed() {
+    let probe = env::temp_dir().join("beet_query-CaseProbe");
+    fs::create_dir_all(&probe).unwrap();
+    let folds_case = Path::new(&probe.to_string_lossy().to_lowercase()).exists();
+
+    let pattern = PathPattern::new(probe.to_str().unwrap());
+    assert_eq!(pattern.case_sensitive(), !folds_case);
+    let shouting = PathBuf::from(probe.to_string_lossy().to_uppercase()).join("a.flac");
+    assert_eq!(pattern.matches(&shouting), folds_case);
+    assert!(pattern.matches(&probe.join("a.flac")));
+}
+
+#[test]
+fn escaped_commas() -> Result<(), Error> {
+    let item = Item {
+        title: "Hello, Goodbye,".to_string(),
+        ..Item::default()
+    };
+    let matches =
+        |query: &str| -> Result<bool, Error> { Ok(query.parse::<Query>()?.match_item(&item)) };
+    assert!(matches(r"title:Hello\,")?);
+    assert!(matches(r"title:Goodbye\,")?);
+    assert!(matches(r"(title:Goodbye\,)")?);
+    assert!(matches(r"title:Hello\, , artist:nobody")?);
+    assert!(!matches("title:Hello\\,xyz")?);
+    assert!(matches("title::,$")?);
+    Ok(())
+}
+
+#[test]
+fn or_queries() -> Result<(), Error> {
+    assert_eq!(matching_items("artist:beatles , artist:aphex")?, [3, 4]);
+    assert_eq!(matching_items("artist:beatles, artist:aphex")?, [3, 4]);
+    // Only a comma at the end of a word separates groups.
+    assert!(matching_items("artist:beatles,artist:aphex")?.is_empty());
+    assert_eq!(
+        matching_items("format:flac title:ready , artist:aphex")?,
+        [1, 4]
+    );
+    assert_eq!(
+        matching_items("(artist:beatles , artist:aphex) year:..1990")?,
+        [3]
+    );
+    assert_eq!(
+        matching_items("((artist:beatles) , (artist:aphex))")?,
+        [3, 4]
+    );
+    assert_eq!(matching_items("title::(Math|Together)")?, [2, 3]);
+    assert_eq!(matching_items("(title::(Math|Together))")?, [2, 3]);
+
+    let query = Query::parse_with_clock("artist:a (year:1999 , year:2002) artist+", &utc())?;
+    assert!(query.keys().all());
+    assert_eq!(query.keys().keys().len(), 2);
+    match &query.keys().keys()[1] {
+        Key::Group(group) => {
+            assert!(!group.all());
+            assert_eq!(group.keys().len(), 2);
+        }
+        key => panic!("expected a group, got {:?}", key),
+    }
+
+    for empty in &["artist:beatles ,", ", artist:aphex", "a , , b", "(a ,) b"] {
+        match empty.parse::<Query>() {
+            Err(Error::EmptyAlternative) => {}
+            other => panic!("{}: {:?}", empty, other),
+        }
+    }
+
+    for unbalanced in &["(artist:a", "artist:a)", "(a , (b)", "a )"] {
+        match unbalanced.parse::<Query>() {
+            Err(Error::UnbalancedParentheses) => {}
+            other => panic!("{}: {:?}", unbalanced, other),
+        }
+    }
+    Ok(())
+}
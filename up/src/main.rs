@@ -2,8 +2,16 @@
 
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
+use std::process;
 use std::sync::{Arc, Mutex};
+#[cfg(target_os = "linux")]
+use std::time::Duration;
 
+use beet_db::config::Config;
+#[cfg(target_os = "linux")]
+use beet_db::watch::Watcher;
+#[cfg(target_os = "linux")]
+use log::{info, warn};
 use structopt::StructOpt;
 use warp::Filter;
 
@@ -12,6 +20,10 @@ mod router;
 
 const LOG_TARGET: &str = "beet_up::api";
 
+/// How long the database must go without writes before it is read again.
+#[cfg(target_os = "linux")]
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
 type Model = Arc<Mutex<model::Model>>;
 
 #[derive(Debug, StructOpt)]
@@ -28,30 +40,86 @@ struct Cli {
     port: u16,
     /// The CORS allowed origin. CORS is off if not provided.
     #[structopt(long)]
+    #[allow(dead_code)]
     cors: Option<String>,
     /// Support credentials when using CORS.
     #[structopt(long, requires = "cors")]
+    #[allow(dead_code)]
     cors_supports_credentials: bool,
     /// Respect forwarded headers when behind a reverse proxy.
     #[structopt(long)]
+    #[allow(dead_code)]
     reverse_proxy: bool,
     /// Include paths in item responses.
     #[structopt(long)]
+    #[allow(dead_code)]
     include_paths: bool,
-    /// Path to your beet database.
+    /// Path to your beet database. Defaults to the `library` in your beets
+    /// configuration.
     #[structopt(parse(from_os_str))]
-    db_path: PathBuf,
+    db_path: Option<PathBuf>,
 }
 
 fn main() {
     pretty_env_logger::init();
     let cli = Cli::from_args();
 
-    let model = model::Model::new(cli.db_path);
+    let db_path = match cli
+        .db_path
+        .map_or_else(|| Config::load().map(|c| c.library), Ok)
+    {
+        Ok(db_path) => db_path,
+        Err(err) => {
+            eprintln!("Could not read beets configuration: {err}");
+            process::exit(1);
+        }
+    };
+
+    let model = match model::Model::new(db_path.clone()) {
+        Ok(model) => Arc::new(Mutex::new(model)),
+        Err(err) => {
+            eprintln!("Could not load library: {err}");
+            process::exit(1);
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    let _watcher = watch(db_path, &model);
 
     let addr = SocketAddr::new(cli.host, cli.port);
-    println!("Now listening at http://{}.", addr);
+    println!("Now listening at http://{addr}.");
+
+    warp::serve(router::router(&model).with(warp::log::log(LOG_TARGET))).run(addr);
+}
+
+/// Keep the model up to date with changes to the database, e.g. by
+/// `beet import`.
+#[cfg(target_os = "linux")]
+fn watch(db_path: PathBuf, model: &Model) -> Option<Watcher> {
+    let watcher = {
+        let current = model.lock().ok()?;
+        let model = Arc::clone(model);
+        Watcher::new(
+            db_path,
+            current.library(),
+            WATCH_DEBOUNCE,
+            move |diff| match diff {
+                Ok(diff) => {
+                    info!("Library changed, updating");
+                    if let Ok(mut model) = model.lock() {
+                        model.apply(&diff);
+                    }
+                }
+                Err(err) => warn!("Could not read library changes: {err}"),
+            },
+        )
+    };
 
-    warp::serve(router::router(&Arc::new(Mutex::new(model))).with(warp::log::log(LOG_TARGET)))
-        .run(addr)
+    match watcher {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            eprintln!("Could not watch library for changes: {err}");
+            None
+        }
+    }
 }
@@ -1,50 +1,48 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::path::PathBuf;
 
-use serde_derive::Serialize;
-
-use beet_db::{read_all, Album, Item};
+#[cfg(target_os = "linux")]
+use beet_db::watch::Diff;
+use beet_db::{multi::Separators, stats::Stats};
+use beet_db::{Album, Error, Item, Library};
 use beet_query::Query;
 
 pub struct Model {
-    albums: Vec<Album>,
-    items: Vec<Item>,
+    library: Library,
     legal_paths: HashSet<PathBuf>,
 }
 
-#[derive(Serialize)]
-pub struct Stats {
-    albums: usize,
-    items: usize,
-}
-
 impl Model {
-    pub fn new(db_path: PathBuf) -> Self {
-        let err_msg = format!("Could not read database at {:?}", db_path);
-        let (albums, items) = read_all(db_path).expect(&err_msg);
-
-        let legal_paths = albums
-            .iter()
-            .filter_map(|Album { artpath, .. }| artpath.clone())
-            .chain(items.iter().map(|Item { path, .. }| path).cloned())
-            .collect();
-
-        Self {
-            albums,
-            items,
+    pub fn new(db_path: PathBuf) -> Result<Self, Error> {
+        let library = Library::open(db_path)?;
+        let legal_paths = legal_paths(&library);
+
+        Ok(Self {
+            library,
             legal_paths,
-        }
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn apply(&mut self, diff: &Diff) {
+        self.library.apply(diff);
+        self.legal_paths = legal_paths(&self.library);
     }
 
     pub fn get_stats(&self) -> Stats {
-        Stats {
-            albums: self.albums.len(),
-            items: self.items.len(),
-        }
+        Stats::new(&self.library, &Separators::default())
     }
 
     pub fn get_all_albums(&self) -> Vec<Album> {
-        self.albums.clone()
+        self.library
+            .albums()
+            .map(|a| a.as_album().clone())
+            .collect()
     }
 
     pub fn check_path(&self, path: &PathBuf) -> bool {
@@ -52,66 +50,66 @@ impl Model {
     }
 
     pub fn get_album_items_id(&self, id: u32) -> Vec<Item> {
-        self.items
-            .iter()
-            .filter(|Item { album_id, .. }| match &album_id {
-                Some(i) if *i == id => true,
-                _ => false,
-            })
-            .cloned()
-            .collect()
+        self.library
+            .album(id)
+            .map(|a| a.items().map(|i| i.as_item().clone()).collect())
+            .unwrap_or_default()
     }
 
     pub fn get_album_id(&self, id: u32) -> Option<Album> {
-        self.albums.iter().find(|a| a.id == id).cloned()
+        self.library.album(id).map(|a| a.as_album().clone())
     }
 
     pub fn get_album_ids(&self, ids: &[u32]) -> Vec<Album> {
-        let s = ids.iter().collect::<HashSet<_>>();
-        self.albums
-            .iter()
-            .filter(|Album { id, .. }| s.contains(id))
-            .cloned()
+        let s = ids.iter().collect::<BTreeSet<_>>();
+        s.into_iter()
+            .filter_map(|&id| self.get_album_id(id))
             .collect()
     }
 
     pub fn get_all_items(&self) -> Vec<Item> {
-        self.items.clone()
+        self.library.items().map(|i| i.as_item().clone()).collect()
     }
 
     pub fn get_item_id(&self, id: u32) -> Option<Item> {
-        self.items.iter().find(|i| i.id == id).cloned()
+        self.library.item(id).map(|i| i.as_item().clone())
     }
 
     pub fn get_item_ids(&self, ids: &[u32]) -> Vec<Item> {
-        let s = ids.iter().collect::<HashSet<_>>();
-        self.items
-            .iter()
-            .filter(|Item { id, .. }| s.contains(id))
-            .cloned()
+        let s = ids.iter().collect::<BTreeSet<_>>();
+        s.into_iter()
+            .filter_map(|&id| self.get_item_id(id))
             .collect()
     }
 
     pub fn get_item_path(&self, pth: &PathBuf) -> Option<Item> {
-        self.items
-            .iter()
-            .find(|Item { path, .. }| path == pth)
-            .cloned()
+        self.library
+            .items()
+            .find(|i| &i.path == pth)
+            .map(|i| i.as_item().clone())
     }
 
     pub fn query_albums(&self, q: &Query) -> Vec<Album> {
-        self.albums
-            .iter()
-            .filter(|album| q.match_album(album))
-            .cloned()
+        self.library
+            .albums()
+            .filter(|album| q.match_album_ref(*album))
+            .map(|album| album.as_album().clone())
             .collect()
     }
 
     pub fn query_items(&self, q: &Query) -> Vec<Item> {
-        self.items
-            .iter()
+        self.library
+            .items()
             .filter(|item| q.match_item(item))
-            .cloned()
+            .map(|item| item.as_item().clone())
             .collect()
     }
 }
+
+fn legal_paths(library: &Library) -> HashSet<PathBuf> {
+    library
+        .albums()
+        .filter_map(|album| album.artpath.clone())
+        .chain(library.items().map(|item| item.path.clone()))
+        .collect()
+}
@@ -1,8 +1,9 @@
 #![allow(clippy::needless_pass_by_value)]
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use url::percent_encoding::{percent_decode, utf8_percent_encode, DEFAULT_ENCODE_SET};
+use url::percent_encoding::{percent_decode, percent_encode, DEFAULT_ENCODE_SET};
 use warp::{
     http::{Response, Uri},
     path::{Peek, Tail},
@@ -24,13 +25,23 @@ fn sync_err<T>(_: T) -> Rejection {
     custom(Error::Sync)
 }
 
+/// Decode a path from a URL, keeping its exact bytes.
+fn decode_path(s: &str) -> PathBuf {
+    beet_db::path::from_bytes(percent_decode(s.as_bytes()).collect())
+}
+
+/// Encode a path as the URL of the `/file` route, keeping its exact bytes.
+fn file_uri(path: &Path) -> Option<Uri> {
+    format!(
+        "/file/{}",
+        percent_encode(&beet_db::path::to_bytes(path), DEFAULT_ENCODE_SET)
+    )
+    .parse()
+    .ok()
+}
+
 pub fn check_path(tail: Peek, model: Model) -> Result<(), Rejection> {
-    let path = PathBuf::from(
-        percent_decode(tail.as_str().as_bytes())
-            .decode_utf8()
-            .map_err(req_err("could not decode path to item"))?
-            .to_string(),
-    );
+    let path = decode_path(tail.as_str());
     model.lock().map_err(sync_err).and_then(|m| {
         if m.check_path(&path) {
             Ok(())
@@ -40,6 +51,17 @@ pub fn check_path(tail: Peek, model: Model) -> Result<(), Rejection> {
     })
 }
 
+/// Serve a file whose path is not valid UTF-8, which `warp::fs` cannot
+/// decode. The path must already have been checked with `check_path`.
+pub fn get_raw_file(tail: Tail) -> Result<impl Reply, Rejection> {
+    let bytes = fs::read(decode_path(tail.as_str())).map_err(|_| not_found())?;
+    Ok(with_header(
+        Response::new(bytes),
+        "content-type",
+        "application/octet-stream",
+    ))
+}
+
 pub fn get_wasm() -> impl Reply {
     with_header(
         Response::new(
@@ -88,14 +110,11 @@ pub fn get_album_art(id: u32, model: Model) -> Result<impl Reply, Rejection> {
         Some(beet_db::Album {
             artpath: Some(path),
             ..
-        }) => Ok(warp::redirect(
-            format!(
-                "/file/{}",
-                utf8_percent_encode(&path.to_string_lossy(), DEFAULT_ENCODE_SET)
-            )
-            .parse::<Uri>()
-            .map_err(req_err("could not encode art path as a valid URI"))?,
-        )),
+        }) => Ok(warp::redirect(file_uri(&path).ok_or_else(|| {
+            custom(Error::BadRequest(
+                "could not encode art path as a valid URI",
+            ))
+        })?)),
         _ => Err(not_found()),
     }
 }
@@ -143,12 +162,7 @@ pub fn get_item_path(path: Tail, model: Model) -> Result<impl Reply, Rejection>
     model
         .lock()
         .map_err(sync_err)?
-        .get_item_path(&PathBuf::from(
-            percent_decode(path.as_str().as_bytes())
-                .decode_utf8()
-                .map_err(req_err("could not decode path to item"))?
-                .to_string(),
-        ))
+        .get_item_path(&decode_path(path.as_str()))
         .ok_or_else(not_found)
         .map(|item| json(&item))
 }
@@ -160,14 +174,9 @@ pub fn get_item_file(id: u32, model: Model) -> Result<impl Reply, Rejection> {
         .get_item_id(id)
         .ok_or_else(not_found)
         .and_then(|beet_db::Item { path, .. }| {
-            Ok(warp::redirect(
-                format!(
-                    "/file/{}",
-                    utf8_percent_encode(&path.to_string_lossy(), DEFAULT_ENCODE_SET)
-                )
-                .parse::<Uri>()
-                .map_err(req_err("could not encode item path as valid URI"))?,
-            ))
+            Ok(warp::redirect(file_uri(&path).ok_or_else(|| {
+                custom(Error::BadRequest("could not encode item path as valid URI"))
+            })?))
         })
 }
 
@@ -20,7 +20,7 @@ pub enum Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::BadRequest(s) => write!(f, "Bad request: {}", s),
+            Error::BadRequest(s) => write!(f, "Bad request: {s}"),
             Error::Sync => write!(f, "Could not acquire lock on data store."),
         }
     }
@@ -58,7 +58,7 @@ fn route_files(model: Model) -> BoxedFilter<(impl Reply,)> {
         .and(db.clone())
         .and_then(handlers::check_path)
         .untuple_one()
-        .and(warp::fs::dir("/"))
+        .and(warp::fs::dir("/").or(path::tail().and_then(handlers::get_raw_file)))
         .boxed()
 }
 
@@ -207,13 +207,13 @@ impl Renderable<Self> for Filter {
 
 impl Filter {
     fn filter_albums(&self) -> impl Iterator<Item = &Album> {
-        let q = self.query.parse::<Query>().unwrap();
+        let q = self.query.parse::<Query>().unwrap_or_default();
 
         self.albums.iter().filter(move |album| q.match_album(album))
     }
 
     fn filter_items(&self) -> impl Iterator<Item = &Item> {
-        let q = self.query.parse::<Query>().unwrap();
+        let q = self.query.parse::<Query>().unwrap_or_default();
 
         self.items.iter().filter(move |item| q.match_item(item))
     }